@@ -0,0 +1,75 @@
+use crate::opcodes::aot::Compiled;
+
+mod task_9;
+
+pub static MODULES: [&Compiled; 1] = [&task_9::COMPILED];
+
+pub fn find(name: &str) -> Option<&'static Compiled> {
+    MODULES.iter().find(|m| m.name == name).cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::aot::translate;
+    use crate::opcodes::Programm;
+    use std::fs;
+    use std::io::Write;
+    use std::process::{Command, Stdio};
+
+    // None when rustfmt is not installed.
+    fn rustfmt(source: &str) -> Option<String> {
+        let mut child = Command::new("rustfmt")
+            .args(["--edition", "2018"])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .ok()?;
+        child
+            .stdin
+            .take()
+            .unwrap()
+            .write_all(source.as_bytes())
+            .unwrap();
+        let output = child.wait_with_output().unwrap();
+        assert!(
+            output.status.success(),
+            "rustfmt rejected the generated code"
+        );
+        Some(String::from_utf8(output.stdout).unwrap())
+    }
+
+    #[test]
+    fn modules_are_up_to_date() {
+        for module in MODULES.iter() {
+            let input = format!("input/{}", module.name);
+            let image = fs::read_to_string(&input)
+                .unwrap()
+                .parse::<Programm>()
+                .unwrap()
+                .image();
+            let path = format!("src/compiled/{}.rs", module.name);
+            let generated = match rustfmt(&translate(module.name, &image)) {
+                Some(generated) => generated,
+                None => {
+                    eprintln!("skipping {}: rustfmt is not on PATH", path);
+                    return;
+                }
+            };
+            assert!(
+                generated == fs::read_to_string(&path).unwrap(),
+                "{} is stale, regenerate it with `aot {}` and rustfmt",
+                path,
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn task_9_matches_the_interpreter() {
+        let module = find("task_9").unwrap();
+        for inputs in &[&[][..], &[1], &[2]] {
+            assert_eq!(module.verify(inputs), Ok(()), "inputs {:?}", inputs);
+        }
+    }
+}
@@ -0,0 +1,1239 @@
+// Generated by `aot` from a 973-cell Intcode image, do not edit.
+use crate::opcodes::aot::{Compiled, Exit, Machine};
+use crate::opcodes::VmError;
+
+pub static IMAGE: [i64; 973] = [
+    1102, 34463338, 34463338, 63, 1007, 63, 34463338, 63, 1005, 63, 53, 1102, 1, 3, 1000, 109, 988,
+    209, 12, 9, 1000, 209, 6, 209, 3, 203, 0, 1008, 1000, 1, 63, 1005, 63, 65, 1008, 1000, 2, 63,
+    1005, 63, 904, 1008, 1000, 0, 63, 1005, 63, 58, 4, 25, 104, 0, 99, 4, 0, 104, 0, 99, 4, 17,
+    104, 0, 99, 0, 0, 1101, 36, 0, 1004, 1102, 28, 1, 1003, 1101, 0, 0, 1020, 1102, 22, 1, 1016,
+    1101, 21, 0, 1015, 1102, 897, 1, 1028, 1101, 0, 815, 1022, 1101, 554, 0, 1027, 1101, 0, 38,
+    1005, 1102, 33, 1, 1008, 1101, 0, 23, 1018, 1101, 826, 0, 1025, 1101, 0, 30, 1013, 1102, 31, 1,
+    1017, 1102, 35, 1, 1010, 1102, 1, 34, 1007, 1102, 1, 892, 1029, 1101, 0, 808, 1023, 1102, 29,
+    1, 1014, 1102, 1, 1, 1021, 1101, 0, 39, 1002, 1101, 0, 561, 1026, 1102, 1, 27, 1009, 1102, 20,
+    1, 1019, 1102, 37, 1, 1011, 1101, 32, 0, 1000, 1102, 1, 26, 1001, 1101, 0, 25, 1012, 1102, 24,
+    1, 1006, 1101, 0, 835, 1024, 109, 10, 21108, 40, 41, 4, 1005, 1014, 201, 1001, 64, 1, 64, 1105,
+    1, 203, 4, 187, 1002, 64, 2, 64, 109, -12, 2101, 0, 9, 63, 1008, 63, 34, 63, 1005, 63, 229, 4,
+    209, 1001, 64, 1, 64, 1105, 1, 229, 1002, 64, 2, 64, 109, -4, 1202, 8, 1, 63, 1008, 63, 39, 63,
+    1005, 63, 255, 4, 235, 1001, 64, 1, 64, 1106, 0, 255, 1002, 64, 2, 64, 109, 12, 1201, 2, 0, 63,
+    1008, 63, 34, 63, 1005, 63, 279, 1001, 64, 1, 64, 1105, 1, 281, 4, 261, 1002, 64, 2, 64, 109,
+    12, 1206, 2, 299, 4, 287, 1001, 64, 1, 64, 1106, 0, 299, 1002, 64, 2, 64, 109, -21, 1202, 7, 1,
+    63, 1008, 63, 34, 63, 1005, 63, 319, 1106, 0, 325, 4, 305, 1001, 64, 1, 64, 1002, 64, 2, 64,
+    109, 5, 1201, -2, 0, 63, 1008, 63, 32, 63, 1005, 63, 347, 4, 331, 1105, 1, 351, 1001, 64, 1,
+    64, 1002, 64, 2, 64, 109, -2, 1208, 3, 28, 63, 1005, 63, 373, 4, 357, 1001, 64, 1, 64, 1106, 0,
+    373, 1002, 64, 2, 64, 109, 5, 2107, 28, 4, 63, 1005, 63, 389, 1106, 0, 395, 4, 379, 1001, 64,
+    1, 64, 1002, 64, 2, 64, 109, 3, 1208, 1, 26, 63, 1005, 63, 415, 1001, 64, 1, 64, 1106, 0, 417,
+    4, 401, 1002, 64, 2, 64, 109, -5, 2101, 0, 0, 63, 1008, 63, 25, 63, 1005, 63, 441, 1001, 64, 1,
+    64, 1105, 1, 443, 4, 423, 1002, 64, 2, 64, 109, 14, 1206, 4, 459, 1001, 64, 1, 64, 1105, 1,
+    461, 4, 449, 1002, 64, 2, 64, 109, -11, 21107, 41, 40, 4, 1005, 1010, 477, 1105, 1, 483, 4,
+    467, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 1, 2107, 23, -1, 63, 1005, 63, 501, 4, 489, 1106,
+    0, 505, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 1, 1207, -4, 37, 63, 1005, 63, 523, 4, 511,
+    1106, 0, 527, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 8, 1205, 5, 545, 4, 533, 1001, 64, 1, 64,
+    1105, 1, 545, 1002, 64, 2, 64, 109, 14, 2106, 0, -3, 1001, 64, 1, 64, 1106, 0, 563, 4, 551,
+    1002, 64, 2, 64, 109, -29, 2108, 32, -1, 63, 1005, 63, 585, 4, 569, 1001, 64, 1, 64, 1105, 1,
+    585, 1002, 64, 2, 64, 109, 19, 21108, 42, 42, -6, 1005, 1014, 603, 4, 591, 1106, 0, 607, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, -12, 1207, -7, 25, 63, 1005, 63, 627, 1001, 64, 1, 64, 1106,
+    0, 629, 4, 613, 1002, 64, 2, 64, 109, 12, 21102, 43, 1, -7, 1008, 1013, 43, 63, 1005, 63, 655,
+    4, 635, 1001, 64, 1, 64, 1105, 1, 655, 1002, 64, 2, 64, 109, -11, 21101, 44, 0, 6, 1008, 1015,
+    46, 63, 1005, 63, 675, 1106, 0, 681, 4, 661, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -1, 21102,
+    45, 1, 7, 1008, 1015, 42, 63, 1005, 63, 701, 1106, 0, 707, 4, 687, 1001, 64, 1, 64, 1002, 64,
+    2, 64, 109, -1, 2102, 1, 2, 63, 1008, 63, 26, 63, 1005, 63, 731, 1001, 64, 1, 64, 1106, 0, 733,
+    4, 713, 1002, 64, 2, 64, 109, 6, 21107, 46, 47, -2, 1005, 1011, 755, 4, 739, 1001, 64, 1, 64,
+    1105, 1, 755, 1002, 64, 2, 64, 109, 2, 21101, 47, 0, -2, 1008, 1013, 47, 63, 1005, 63, 777, 4,
+    761, 1106, 0, 781, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, 10, 1205, -5, 793, 1106, 0, 799, 4,
+    787, 1001, 64, 1, 64, 1002, 64, 2, 64, 109, -1, 2105, 1, -1, 1001, 64, 1, 64, 1105, 1, 817, 4,
+    805, 1002, 64, 2, 64, 109, 9, 2105, 1, -9, 4, 823, 1001, 64, 1, 64, 1105, 1, 835, 1002, 64, 2,
+    64, 109, -36, 2108, 38, 7, 63, 1005, 63, 855, 1001, 64, 1, 64, 1106, 0, 857, 4, 841, 1002, 64,
+    2, 64, 109, 13, 2102, 1, -6, 63, 1008, 63, 36, 63, 1005, 63, 879, 4, 863, 1106, 0, 883, 1001,
+    64, 1, 64, 1002, 64, 2, 64, 109, 10, 2106, 0, 8, 4, 889, 1105, 1, 901, 1001, 64, 1, 64, 4, 64,
+    99, 21101, 0, 27, 1, 21101, 915, 0, 0, 1106, 0, 922, 21201, 1, 49329, 1, 204, 1, 99, 109, 3,
+    1207, -2, 3, 63, 1005, 63, 964, 21201, -2, -1, 1, 21102, 1, 942, 0, 1105, 1, 922, 21201, 1, 0,
+    -1, 21201, -2, -3, 1, 21102, 957, 1, 0, 1106, 0, 922, 22201, 1, -1, -2, 1105, 1, 968, 22102, 1,
+    -2, -2, 109, -3, 2105, 1, 0,
+];
+
+pub static COMPILED: Compiled = Compiled {
+    name: "task_9",
+    image: &IMAGE,
+    run,
+};
+
+fn run(m: &mut Machine) -> Result<Exit, VmError> {
+    loop {
+        match m.pc {
+            0 if m.clean(0, 4) => {
+                m.set(63, 1187721666102244)?;
+                m.pc = 4;
+            }
+            4 if m.clean(4, 4) => {
+                m.set(63, (m.get(63) < 34463338) as i64)?;
+                m.pc = 8;
+            }
+            8 if m.clean(8, 3) => {
+                m.pc = if m.get(63) != 0 { 53 } else { 11 };
+            }
+            11 if m.clean(11, 4) => {
+                m.set(1000, 3)?;
+                m.pc = 15;
+            }
+            15 if m.clean(15, 2) => {
+                m.adjust(988)?;
+                m.pc = 17;
+            }
+            17 if m.clean(17, 2) => {
+                m.adjust(m.get(m.relative(12)?))?;
+                m.pc = 19;
+            }
+            19 if m.clean(19, 2) => {
+                m.adjust(m.get(1000))?;
+                m.pc = 21;
+            }
+            21 if m.clean(21, 2) => {
+                m.adjust(m.get(m.relative(6)?))?;
+                m.pc = 23;
+            }
+            23 if m.clean(23, 2) => {
+                m.adjust(m.get(m.relative(3)?))?;
+                m.pc = 25;
+            }
+            25 if m.clean(25, 2) => {
+                let input = match m.input() {
+                    Some(input) => input,
+                    None => return Ok(Exit::NeedInput),
+                };
+                let v = input;
+                m.set(m.relative(0)?, v)?;
+                m.pc = 27;
+            }
+            27 if m.clean(27, 4) => {
+                m.set(63, (m.get(1000) == 1) as i64)?;
+                m.pc = 31;
+            }
+            31 if m.clean(31, 3) => {
+                m.pc = if m.get(63) != 0 { 65 } else { 34 };
+            }
+            34 if m.clean(34, 4) => {
+                m.set(63, (m.get(1000) == 2) as i64)?;
+                m.pc = 38;
+            }
+            38 if m.clean(38, 3) => {
+                m.pc = if m.get(63) != 0 { 904 } else { 41 };
+            }
+            41 if m.clean(41, 4) => {
+                m.set(63, (m.get(1000) == 0) as i64)?;
+                m.pc = 45;
+            }
+            45 if m.clean(45, 3) => {
+                m.pc = if m.get(63) != 0 { 58 } else { 48 };
+            }
+            48 if m.clean(48, 2) => {
+                m.output(m.get(25));
+                m.pc = 50;
+            }
+            50 if m.clean(50, 2) => {
+                m.output(0);
+                m.pc = 52;
+            }
+            52 if m.clean(52, 1) => {
+                return Ok(m.halt());
+            }
+            53 if m.clean(53, 2) => {
+                m.output(m.get(0));
+                m.pc = 55;
+            }
+            55 if m.clean(55, 2) => {
+                m.output(0);
+                m.pc = 57;
+            }
+            57 if m.clean(57, 1) => {
+                return Ok(m.halt());
+            }
+            58 if m.clean(58, 2) => {
+                m.output(m.get(17));
+                m.pc = 60;
+            }
+            60 if m.clean(60, 2) => {
+                m.output(0);
+                m.pc = 62;
+            }
+            62 if m.clean(62, 1) => {
+                return Ok(m.halt());
+            }
+            65 if m.clean(65, 4) => {
+                m.set(1004, 36)?;
+                m.pc = 69;
+            }
+            69 if m.clean(69, 4) => {
+                m.set(1003, 28)?;
+                m.pc = 73;
+            }
+            73 if m.clean(73, 4) => {
+                m.set(1020, 0)?;
+                m.pc = 77;
+            }
+            77 if m.clean(77, 4) => {
+                m.set(1016, 22)?;
+                m.pc = 81;
+            }
+            81 if m.clean(81, 4) => {
+                m.set(1015, 21)?;
+                m.pc = 85;
+            }
+            85 if m.clean(85, 4) => {
+                m.set(1028, 897)?;
+                m.pc = 89;
+            }
+            89 if m.clean(89, 4) => {
+                m.set(1022, 815)?;
+                m.pc = 93;
+            }
+            93 if m.clean(93, 4) => {
+                m.set(1027, 554)?;
+                m.pc = 97;
+            }
+            97 if m.clean(97, 4) => {
+                m.set(1005, 38)?;
+                m.pc = 101;
+            }
+            101 if m.clean(101, 4) => {
+                m.set(1008, 33)?;
+                m.pc = 105;
+            }
+            105 if m.clean(105, 4) => {
+                m.set(1018, 23)?;
+                m.pc = 109;
+            }
+            109 if m.clean(109, 4) => {
+                m.set(1025, 826)?;
+                m.pc = 113;
+            }
+            113 if m.clean(113, 4) => {
+                m.set(1013, 30)?;
+                m.pc = 117;
+            }
+            117 if m.clean(117, 4) => {
+                m.set(1017, 31)?;
+                m.pc = 121;
+            }
+            121 if m.clean(121, 4) => {
+                m.set(1010, 35)?;
+                m.pc = 125;
+            }
+            125 if m.clean(125, 4) => {
+                m.set(1007, 34)?;
+                m.pc = 129;
+            }
+            129 if m.clean(129, 4) => {
+                m.set(1029, 892)?;
+                m.pc = 133;
+            }
+            133 if m.clean(133, 4) => {
+                m.set(1023, 808)?;
+                m.pc = 137;
+            }
+            137 if m.clean(137, 4) => {
+                m.set(1014, 29)?;
+                m.pc = 141;
+            }
+            141 if m.clean(141, 4) => {
+                m.set(1021, 1)?;
+                m.pc = 145;
+            }
+            145 if m.clean(145, 4) => {
+                m.set(1002, 39)?;
+                m.pc = 149;
+            }
+            149 if m.clean(149, 4) => {
+                m.set(1026, 561)?;
+                m.pc = 153;
+            }
+            153 if m.clean(153, 4) => {
+                m.set(1009, 27)?;
+                m.pc = 157;
+            }
+            157 if m.clean(157, 4) => {
+                m.set(1019, 20)?;
+                m.pc = 161;
+            }
+            161 if m.clean(161, 4) => {
+                m.set(1011, 37)?;
+                m.pc = 165;
+            }
+            165 if m.clean(165, 4) => {
+                m.set(1000, 32)?;
+                m.pc = 169;
+            }
+            169 if m.clean(169, 4) => {
+                m.set(1001, 26)?;
+                m.pc = 173;
+            }
+            173 if m.clean(173, 4) => {
+                m.set(1012, 25)?;
+                m.pc = 177;
+            }
+            177 if m.clean(177, 4) => {
+                m.set(1006, 24)?;
+                m.pc = 181;
+            }
+            181 if m.clean(181, 4) => {
+                m.set(1024, 835)?;
+                m.pc = 185;
+            }
+            185 if m.clean(185, 2) => {
+                m.adjust(10)?;
+                m.pc = 187;
+            }
+            187 if m.clean(187, 4) => {
+                let v = 0;
+                m.set(m.relative(4)?, v)?;
+                m.pc = 191;
+            }
+            191 if m.clean(191, 3) => {
+                m.pc = if m.get(1014) != 0 { 201 } else { 194 };
+            }
+            194 if m.clean(194, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 198;
+            }
+            198 if m.clean(198, 3) => {
+                m.pc = 203;
+            }
+            201 if m.clean(201, 2) => {
+                m.output(m.get(187));
+                m.pc = 203;
+            }
+            203 if m.clean(203, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 207;
+            }
+            207 if m.clean(207, 2) => {
+                m.adjust(-12)?;
+                m.pc = 209;
+            }
+            209 if m.clean(209, 4) => {
+                m.set(63, m.get(m.relative(9)?).wrapping_add(0))?;
+                m.pc = 213;
+            }
+            213 if m.clean(213, 4) => {
+                m.set(63, (m.get(63) == 34) as i64)?;
+                m.pc = 217;
+            }
+            217 if m.clean(217, 3) => {
+                m.pc = if m.get(63) != 0 { 229 } else { 220 };
+            }
+            220 if m.clean(220, 2) => {
+                m.output(m.get(209));
+                m.pc = 222;
+            }
+            222 if m.clean(222, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 226;
+            }
+            226 if m.clean(226, 3) => {
+                m.pc = 229;
+            }
+            229 if m.clean(229, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 233;
+            }
+            233 if m.clean(233, 2) => {
+                m.adjust(-4)?;
+                m.pc = 235;
+            }
+            235 if m.clean(235, 4) => {
+                m.set(63, m.get(m.relative(8)?).wrapping_mul(1))?;
+                m.pc = 239;
+            }
+            239 if m.clean(239, 4) => {
+                m.set(63, (m.get(63) == 39) as i64)?;
+                m.pc = 243;
+            }
+            243 if m.clean(243, 3) => {
+                m.pc = if m.get(63) != 0 { 255 } else { 246 };
+            }
+            246 if m.clean(246, 2) => {
+                m.output(m.get(235));
+                m.pc = 248;
+            }
+            248 if m.clean(248, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 252;
+            }
+            252 if m.clean(252, 3) => {
+                m.pc = 255;
+            }
+            255 if m.clean(255, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 259;
+            }
+            259 if m.clean(259, 2) => {
+                m.adjust(12)?;
+                m.pc = 261;
+            }
+            261 if m.clean(261, 4) => {
+                m.set(63, m.get(m.relative(2)?).wrapping_add(0))?;
+                m.pc = 265;
+            }
+            265 if m.clean(265, 4) => {
+                m.set(63, (m.get(63) == 34) as i64)?;
+                m.pc = 269;
+            }
+            269 if m.clean(269, 3) => {
+                m.pc = if m.get(63) != 0 { 279 } else { 272 };
+            }
+            272 if m.clean(272, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 276;
+            }
+            276 if m.clean(276, 3) => {
+                m.pc = 281;
+            }
+            279 if m.clean(279, 2) => {
+                m.output(m.get(261));
+                m.pc = 281;
+            }
+            281 if m.clean(281, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 285;
+            }
+            285 if m.clean(285, 2) => {
+                m.adjust(12)?;
+                m.pc = 287;
+            }
+            287 if m.clean(287, 3) => {
+                m.pc = if m.get(m.relative(2)?) == 0 { 299 } else { 290 };
+            }
+            290 if m.clean(290, 2) => {
+                m.output(m.get(287));
+                m.pc = 292;
+            }
+            292 if m.clean(292, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 296;
+            }
+            296 if m.clean(296, 3) => {
+                m.pc = 299;
+            }
+            299 if m.clean(299, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 303;
+            }
+            303 if m.clean(303, 2) => {
+                m.adjust(-21)?;
+                m.pc = 305;
+            }
+            305 if m.clean(305, 4) => {
+                m.set(63, m.get(m.relative(7)?).wrapping_mul(1))?;
+                m.pc = 309;
+            }
+            309 if m.clean(309, 4) => {
+                m.set(63, (m.get(63) == 34) as i64)?;
+                m.pc = 313;
+            }
+            313 if m.clean(313, 3) => {
+                m.pc = if m.get(63) != 0 { 319 } else { 316 };
+            }
+            316 if m.clean(316, 3) => {
+                m.pc = 325;
+            }
+            319 if m.clean(319, 2) => {
+                m.output(m.get(305));
+                m.pc = 321;
+            }
+            321 if m.clean(321, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 325;
+            }
+            325 if m.clean(325, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 329;
+            }
+            329 if m.clean(329, 2) => {
+                m.adjust(5)?;
+                m.pc = 331;
+            }
+            331 if m.clean(331, 4) => {
+                m.set(63, m.get(m.relative(-2)?).wrapping_add(0))?;
+                m.pc = 335;
+            }
+            335 if m.clean(335, 4) => {
+                m.set(63, (m.get(63) == 32) as i64)?;
+                m.pc = 339;
+            }
+            339 if m.clean(339, 3) => {
+                m.pc = if m.get(63) != 0 { 347 } else { 342 };
+            }
+            342 if m.clean(342, 2) => {
+                m.output(m.get(331));
+                m.pc = 344;
+            }
+            344 if m.clean(344, 3) => {
+                m.pc = 351;
+            }
+            347 if m.clean(347, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 351;
+            }
+            351 if m.clean(351, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 355;
+            }
+            355 if m.clean(355, 2) => {
+                m.adjust(-2)?;
+                m.pc = 357;
+            }
+            357 if m.clean(357, 4) => {
+                m.set(63, (m.get(m.relative(3)?) == 28) as i64)?;
+                m.pc = 361;
+            }
+            361 if m.clean(361, 3) => {
+                m.pc = if m.get(63) != 0 { 373 } else { 364 };
+            }
+            364 if m.clean(364, 2) => {
+                m.output(m.get(357));
+                m.pc = 366;
+            }
+            366 if m.clean(366, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 370;
+            }
+            370 if m.clean(370, 3) => {
+                m.pc = 373;
+            }
+            373 if m.clean(373, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 377;
+            }
+            377 if m.clean(377, 2) => {
+                m.adjust(5)?;
+                m.pc = 379;
+            }
+            379 if m.clean(379, 4) => {
+                m.set(63, (28 < m.get(m.relative(4)?)) as i64)?;
+                m.pc = 383;
+            }
+            383 if m.clean(383, 3) => {
+                m.pc = if m.get(63) != 0 { 389 } else { 386 };
+            }
+            386 if m.clean(386, 3) => {
+                m.pc = 395;
+            }
+            389 if m.clean(389, 2) => {
+                m.output(m.get(379));
+                m.pc = 391;
+            }
+            391 if m.clean(391, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 395;
+            }
+            395 if m.clean(395, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 399;
+            }
+            399 if m.clean(399, 2) => {
+                m.adjust(3)?;
+                m.pc = 401;
+            }
+            401 if m.clean(401, 4) => {
+                m.set(63, (m.get(m.relative(1)?) == 26) as i64)?;
+                m.pc = 405;
+            }
+            405 if m.clean(405, 3) => {
+                m.pc = if m.get(63) != 0 { 415 } else { 408 };
+            }
+            408 if m.clean(408, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 412;
+            }
+            412 if m.clean(412, 3) => {
+                m.pc = 417;
+            }
+            415 if m.clean(415, 2) => {
+                m.output(m.get(401));
+                m.pc = 417;
+            }
+            417 if m.clean(417, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 421;
+            }
+            421 if m.clean(421, 2) => {
+                m.adjust(-5)?;
+                m.pc = 423;
+            }
+            423 if m.clean(423, 4) => {
+                m.set(63, m.get(m.relative(0)?).wrapping_add(0))?;
+                m.pc = 427;
+            }
+            427 if m.clean(427, 4) => {
+                m.set(63, (m.get(63) == 25) as i64)?;
+                m.pc = 431;
+            }
+            431 if m.clean(431, 3) => {
+                m.pc = if m.get(63) != 0 { 441 } else { 434 };
+            }
+            434 if m.clean(434, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 438;
+            }
+            438 if m.clean(438, 3) => {
+                m.pc = 443;
+            }
+            441 if m.clean(441, 2) => {
+                m.output(m.get(423));
+                m.pc = 443;
+            }
+            443 if m.clean(443, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 447;
+            }
+            447 if m.clean(447, 2) => {
+                m.adjust(14)?;
+                m.pc = 449;
+            }
+            449 if m.clean(449, 3) => {
+                m.pc = if m.get(m.relative(4)?) == 0 { 459 } else { 452 };
+            }
+            452 if m.clean(452, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 456;
+            }
+            456 if m.clean(456, 3) => {
+                m.pc = 461;
+            }
+            459 if m.clean(459, 2) => {
+                m.output(m.get(449));
+                m.pc = 461;
+            }
+            461 if m.clean(461, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 465;
+            }
+            465 if m.clean(465, 2) => {
+                m.adjust(-11)?;
+                m.pc = 467;
+            }
+            467 if m.clean(467, 4) => {
+                let v = 0;
+                m.set(m.relative(4)?, v)?;
+                m.pc = 471;
+            }
+            471 if m.clean(471, 3) => {
+                m.pc = if m.get(1010) != 0 { 477 } else { 474 };
+            }
+            474 if m.clean(474, 3) => {
+                m.pc = 483;
+            }
+            477 if m.clean(477, 2) => {
+                m.output(m.get(467));
+                m.pc = 479;
+            }
+            479 if m.clean(479, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 483;
+            }
+            483 if m.clean(483, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 487;
+            }
+            487 if m.clean(487, 2) => {
+                m.adjust(1)?;
+                m.pc = 489;
+            }
+            489 if m.clean(489, 4) => {
+                m.set(63, (23 < m.get(m.relative(-1)?)) as i64)?;
+                m.pc = 493;
+            }
+            493 if m.clean(493, 3) => {
+                m.pc = if m.get(63) != 0 { 501 } else { 496 };
+            }
+            496 if m.clean(496, 2) => {
+                m.output(m.get(489));
+                m.pc = 498;
+            }
+            498 if m.clean(498, 3) => {
+                m.pc = 505;
+            }
+            501 if m.clean(501, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 505;
+            }
+            505 if m.clean(505, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 509;
+            }
+            509 if m.clean(509, 2) => {
+                m.adjust(1)?;
+                m.pc = 511;
+            }
+            511 if m.clean(511, 4) => {
+                m.set(63, (m.get(m.relative(-4)?) < 37) as i64)?;
+                m.pc = 515;
+            }
+            515 if m.clean(515, 3) => {
+                m.pc = if m.get(63) != 0 { 523 } else { 518 };
+            }
+            518 if m.clean(518, 2) => {
+                m.output(m.get(511));
+                m.pc = 520;
+            }
+            520 if m.clean(520, 3) => {
+                m.pc = 527;
+            }
+            523 if m.clean(523, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 527;
+            }
+            527 if m.clean(527, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 531;
+            }
+            531 if m.clean(531, 2) => {
+                m.adjust(8)?;
+                m.pc = 533;
+            }
+            533 if m.clean(533, 3) => {
+                m.pc = if m.get(m.relative(5)?) != 0 { 545 } else { 536 };
+            }
+            536 if m.clean(536, 2) => {
+                m.output(m.get(533));
+                m.pc = 538;
+            }
+            538 if m.clean(538, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 542;
+            }
+            542 if m.clean(542, 3) => {
+                m.pc = 545;
+            }
+            545 if m.clean(545, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 549;
+            }
+            549 if m.clean(549, 2) => {
+                m.adjust(14)?;
+                m.pc = 551;
+            }
+            551 if m.clean(551, 3) => {
+                m.pc = m.target(m.get(m.relative(-3)?))?;
+            }
+            554 if m.clean(554, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 558;
+            }
+            558 if m.clean(558, 3) => {
+                m.pc = 563;
+            }
+            561 if m.clean(561, 2) => {
+                m.output(m.get(551));
+                m.pc = 563;
+            }
+            563 if m.clean(563, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 567;
+            }
+            567 if m.clean(567, 2) => {
+                m.adjust(-29)?;
+                m.pc = 569;
+            }
+            569 if m.clean(569, 4) => {
+                m.set(63, (32 == m.get(m.relative(-1)?)) as i64)?;
+                m.pc = 573;
+            }
+            573 if m.clean(573, 3) => {
+                m.pc = if m.get(63) != 0 { 585 } else { 576 };
+            }
+            576 if m.clean(576, 2) => {
+                m.output(m.get(569));
+                m.pc = 578;
+            }
+            578 if m.clean(578, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 582;
+            }
+            582 if m.clean(582, 3) => {
+                m.pc = 585;
+            }
+            585 if m.clean(585, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 589;
+            }
+            589 if m.clean(589, 2) => {
+                m.adjust(19)?;
+                m.pc = 591;
+            }
+            591 if m.clean(591, 4) => {
+                let v = 1;
+                m.set(m.relative(-6)?, v)?;
+                m.pc = 595;
+            }
+            595 if m.clean(595, 3) => {
+                m.pc = if m.get(1014) != 0 { 603 } else { 598 };
+            }
+            598 if m.clean(598, 2) => {
+                m.output(m.get(591));
+                m.pc = 600;
+            }
+            600 if m.clean(600, 3) => {
+                m.pc = 607;
+            }
+            603 if m.clean(603, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 607;
+            }
+            607 if m.clean(607, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 611;
+            }
+            611 if m.clean(611, 2) => {
+                m.adjust(-12)?;
+                m.pc = 613;
+            }
+            613 if m.clean(613, 4) => {
+                m.set(63, (m.get(m.relative(-7)?) < 25) as i64)?;
+                m.pc = 617;
+            }
+            617 if m.clean(617, 3) => {
+                m.pc = if m.get(63) != 0 { 627 } else { 620 };
+            }
+            620 if m.clean(620, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 624;
+            }
+            624 if m.clean(624, 3) => {
+                m.pc = 629;
+            }
+            627 if m.clean(627, 2) => {
+                m.output(m.get(613));
+                m.pc = 629;
+            }
+            629 if m.clean(629, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 633;
+            }
+            633 if m.clean(633, 2) => {
+                m.adjust(12)?;
+                m.pc = 635;
+            }
+            635 if m.clean(635, 4) => {
+                let v = 43;
+                m.set(m.relative(-7)?, v)?;
+                m.pc = 639;
+            }
+            639 if m.clean(639, 4) => {
+                m.set(63, (m.get(1013) == 43) as i64)?;
+                m.pc = 643;
+            }
+            643 if m.clean(643, 3) => {
+                m.pc = if m.get(63) != 0 { 655 } else { 646 };
+            }
+            646 if m.clean(646, 2) => {
+                m.output(m.get(635));
+                m.pc = 648;
+            }
+            648 if m.clean(648, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 652;
+            }
+            652 if m.clean(652, 3) => {
+                m.pc = 655;
+            }
+            655 if m.clean(655, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 659;
+            }
+            659 if m.clean(659, 2) => {
+                m.adjust(-11)?;
+                m.pc = 661;
+            }
+            661 if m.clean(661, 4) => {
+                let v = 44;
+                m.set(m.relative(6)?, v)?;
+                m.pc = 665;
+            }
+            665 if m.clean(665, 4) => {
+                m.set(63, (m.get(1015) == 46) as i64)?;
+                m.pc = 669;
+            }
+            669 if m.clean(669, 3) => {
+                m.pc = if m.get(63) != 0 { 675 } else { 672 };
+            }
+            672 if m.clean(672, 3) => {
+                m.pc = 681;
+            }
+            675 if m.clean(675, 2) => {
+                m.output(m.get(661));
+                m.pc = 677;
+            }
+            677 if m.clean(677, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 681;
+            }
+            681 if m.clean(681, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 685;
+            }
+            685 if m.clean(685, 2) => {
+                m.adjust(-1)?;
+                m.pc = 687;
+            }
+            687 if m.clean(687, 4) => {
+                let v = 45;
+                m.set(m.relative(7)?, v)?;
+                m.pc = 691;
+            }
+            691 if m.clean(691, 4) => {
+                m.set(63, (m.get(1015) == 42) as i64)?;
+                m.pc = 695;
+            }
+            695 if m.clean(695, 3) => {
+                m.pc = if m.get(63) != 0 { 701 } else { 698 };
+            }
+            698 if m.clean(698, 3) => {
+                m.pc = 707;
+            }
+            701 if m.clean(701, 2) => {
+                m.output(m.get(687));
+                m.pc = 703;
+            }
+            703 if m.clean(703, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 707;
+            }
+            707 if m.clean(707, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 711;
+            }
+            711 if m.clean(711, 2) => {
+                m.adjust(-1)?;
+                m.pc = 713;
+            }
+            713 if m.clean(713, 4) => {
+                m.set(63, m.get(m.relative(2)?).wrapping_mul(1))?;
+                m.pc = 717;
+            }
+            717 if m.clean(717, 4) => {
+                m.set(63, (m.get(63) == 26) as i64)?;
+                m.pc = 721;
+            }
+            721 if m.clean(721, 3) => {
+                m.pc = if m.get(63) != 0 { 731 } else { 724 };
+            }
+            724 if m.clean(724, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 728;
+            }
+            728 if m.clean(728, 3) => {
+                m.pc = 733;
+            }
+            731 if m.clean(731, 2) => {
+                m.output(m.get(713));
+                m.pc = 733;
+            }
+            733 if m.clean(733, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 737;
+            }
+            737 if m.clean(737, 2) => {
+                m.adjust(6)?;
+                m.pc = 739;
+            }
+            739 if m.clean(739, 4) => {
+                let v = 1;
+                m.set(m.relative(-2)?, v)?;
+                m.pc = 743;
+            }
+            743 if m.clean(743, 3) => {
+                m.pc = if m.get(1011) != 0 { 755 } else { 746 };
+            }
+            746 if m.clean(746, 2) => {
+                m.output(m.get(739));
+                m.pc = 748;
+            }
+            748 if m.clean(748, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 752;
+            }
+            752 if m.clean(752, 3) => {
+                m.pc = 755;
+            }
+            755 if m.clean(755, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 759;
+            }
+            759 if m.clean(759, 2) => {
+                m.adjust(2)?;
+                m.pc = 761;
+            }
+            761 if m.clean(761, 4) => {
+                let v = 47;
+                m.set(m.relative(-2)?, v)?;
+                m.pc = 765;
+            }
+            765 if m.clean(765, 4) => {
+                m.set(63, (m.get(1013) == 47) as i64)?;
+                m.pc = 769;
+            }
+            769 if m.clean(769, 3) => {
+                m.pc = if m.get(63) != 0 { 777 } else { 772 };
+            }
+            772 if m.clean(772, 2) => {
+                m.output(m.get(761));
+                m.pc = 774;
+            }
+            774 if m.clean(774, 3) => {
+                m.pc = 781;
+            }
+            777 if m.clean(777, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 781;
+            }
+            781 if m.clean(781, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 785;
+            }
+            785 if m.clean(785, 2) => {
+                m.adjust(10)?;
+                m.pc = 787;
+            }
+            787 if m.clean(787, 3) => {
+                m.pc = if m.get(m.relative(-5)?) != 0 {
+                    793
+                } else {
+                    790
+                };
+            }
+            790 if m.clean(790, 3) => {
+                m.pc = 799;
+            }
+            793 if m.clean(793, 2) => {
+                m.output(m.get(787));
+                m.pc = 795;
+            }
+            795 if m.clean(795, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 799;
+            }
+            799 if m.clean(799, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 803;
+            }
+            803 if m.clean(803, 2) => {
+                m.adjust(-1)?;
+                m.pc = 805;
+            }
+            805 if m.clean(805, 3) => {
+                m.pc = m.target(m.get(m.relative(-1)?))?;
+            }
+            808 if m.clean(808, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 812;
+            }
+            812 if m.clean(812, 3) => {
+                m.pc = 817;
+            }
+            815 if m.clean(815, 2) => {
+                m.output(m.get(805));
+                m.pc = 817;
+            }
+            817 if m.clean(817, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 821;
+            }
+            821 if m.clean(821, 2) => {
+                m.adjust(9)?;
+                m.pc = 823;
+            }
+            823 if m.clean(823, 3) => {
+                m.pc = m.target(m.get(m.relative(-9)?))?;
+            }
+            826 if m.clean(826, 2) => {
+                m.output(m.get(823));
+                m.pc = 828;
+            }
+            828 if m.clean(828, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 832;
+            }
+            832 if m.clean(832, 3) => {
+                m.pc = 835;
+            }
+            835 if m.clean(835, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 839;
+            }
+            839 if m.clean(839, 2) => {
+                m.adjust(-36)?;
+                m.pc = 841;
+            }
+            841 if m.clean(841, 4) => {
+                m.set(63, (38 == m.get(m.relative(7)?)) as i64)?;
+                m.pc = 845;
+            }
+            845 if m.clean(845, 3) => {
+                m.pc = if m.get(63) != 0 { 855 } else { 848 };
+            }
+            848 if m.clean(848, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 852;
+            }
+            852 if m.clean(852, 3) => {
+                m.pc = 857;
+            }
+            855 if m.clean(855, 2) => {
+                m.output(m.get(841));
+                m.pc = 857;
+            }
+            857 if m.clean(857, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 861;
+            }
+            861 if m.clean(861, 2) => {
+                m.adjust(13)?;
+                m.pc = 863;
+            }
+            863 if m.clean(863, 4) => {
+                m.set(63, m.get(m.relative(-6)?).wrapping_mul(1))?;
+                m.pc = 867;
+            }
+            867 if m.clean(867, 4) => {
+                m.set(63, (m.get(63) == 36) as i64)?;
+                m.pc = 871;
+            }
+            871 if m.clean(871, 3) => {
+                m.pc = if m.get(63) != 0 { 879 } else { 874 };
+            }
+            874 if m.clean(874, 2) => {
+                m.output(m.get(863));
+                m.pc = 876;
+            }
+            876 if m.clean(876, 3) => {
+                m.pc = 883;
+            }
+            879 if m.clean(879, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 883;
+            }
+            883 if m.clean(883, 4) => {
+                m.set(64, m.get(64).wrapping_mul(2))?;
+                m.pc = 887;
+            }
+            887 if m.clean(887, 2) => {
+                m.adjust(10)?;
+                m.pc = 889;
+            }
+            889 if m.clean(889, 3) => {
+                m.pc = m.target(m.get(m.relative(8)?))?;
+            }
+            892 if m.clean(892, 2) => {
+                m.output(m.get(889));
+                m.pc = 894;
+            }
+            894 if m.clean(894, 3) => {
+                m.pc = 901;
+            }
+            897 if m.clean(897, 4) => {
+                m.set(64, m.get(64).wrapping_add(1))?;
+                m.pc = 901;
+            }
+            901 if m.clean(901, 2) => {
+                m.output(m.get(64));
+                m.pc = 903;
+            }
+            903 if m.clean(903, 1) => {
+                return Ok(m.halt());
+            }
+            904 if m.clean(904, 4) => {
+                let v = 27;
+                m.set(m.relative(1)?, v)?;
+                m.pc = 908;
+            }
+            908 if m.clean(908, 4) => {
+                let v = 915;
+                m.set(m.relative(0)?, v)?;
+                m.pc = 912;
+            }
+            912 if m.clean(912, 3) => {
+                m.pc = 922;
+            }
+            915 if m.clean(915, 4) => {
+                let v = m.get(m.relative(1)?).wrapping_add(49329);
+                m.set(m.relative(1)?, v)?;
+                m.pc = 919;
+            }
+            919 if m.clean(919, 2) => {
+                m.output(m.get(m.relative(1)?));
+                m.pc = 921;
+            }
+            921 if m.clean(921, 1) => {
+                return Ok(m.halt());
+            }
+            922 if m.clean(922, 2) => {
+                m.adjust(3)?;
+                m.pc = 924;
+            }
+            924 if m.clean(924, 4) => {
+                m.set(63, (m.get(m.relative(-2)?) < 3) as i64)?;
+                m.pc = 928;
+            }
+            928 if m.clean(928, 3) => {
+                m.pc = if m.get(63) != 0 { 964 } else { 931 };
+            }
+            931 if m.clean(931, 4) => {
+                let v = m.get(m.relative(-2)?).wrapping_add(-1);
+                m.set(m.relative(1)?, v)?;
+                m.pc = 935;
+            }
+            935 if m.clean(935, 4) => {
+                let v = 942;
+                m.set(m.relative(0)?, v)?;
+                m.pc = 939;
+            }
+            939 if m.clean(939, 3) => {
+                m.pc = 922;
+            }
+            942 if m.clean(942, 4) => {
+                let v = m.get(m.relative(1)?).wrapping_add(0);
+                m.set(m.relative(-1)?, v)?;
+                m.pc = 946;
+            }
+            946 if m.clean(946, 4) => {
+                let v = m.get(m.relative(-2)?).wrapping_add(-3);
+                m.set(m.relative(1)?, v)?;
+                m.pc = 950;
+            }
+            950 if m.clean(950, 4) => {
+                let v = 957;
+                m.set(m.relative(0)?, v)?;
+                m.pc = 954;
+            }
+            954 if m.clean(954, 3) => {
+                m.pc = 922;
+            }
+            957 if m.clean(957, 4) => {
+                let v = m.get(m.relative(1)?).wrapping_add(m.get(m.relative(-1)?));
+                m.set(m.relative(-2)?, v)?;
+                m.pc = 961;
+            }
+            961 if m.clean(961, 3) => {
+                m.pc = 968;
+            }
+            964 if m.clean(964, 4) => {
+                let v = m.get(m.relative(-2)?).wrapping_mul(1);
+                m.set(m.relative(-2)?, v)?;
+                m.pc = 968;
+            }
+            968 if m.clean(968, 2) => {
+                m.adjust(-3)?;
+                m.pc = 970;
+            }
+            970 if m.clean(970, 3) => {
+                m.pc = m.target(m.get(m.relative(0)?))?;
+            }
+            _ => {
+                if let Some(exit) = m.interpret()? {
+                    return Ok(exit);
+                }
+            }
+        }
+    }
+}
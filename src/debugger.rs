@@ -0,0 +1,314 @@
+use crate::opcodes::disasm;
+use crate::opcodes::watch::{Access, WatchAction};
+use crate::opcodes::{Programm, StepOutcome, VmError};
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read, Write};
+use termion::color;
+use termion::style;
+
+const HELP: &str = "\
+s [n]            step n instructions (default 1)
+c                continue until breakpoint, input request or halt
+b <addr|OP>      break on address or opcode kind (ADD, MUL, IN, OUT, JT, JF, LT, EQ, ARB, HLT)
+d <addr|OP>      delete breakpoint
+bl               list breakpoints
+watch <r|w|p> <addr> [end]
+                 stop on reads, writes or patches of executed code in a range
+watches          list watchpoints
+unwatch <n>      delete watchpoint n
+x <addr> [n]     examine n memory cells (default 8, at most 4096)
+w <addr> <value> write memory cell
+rb [value]       show or set relative base
+i <v>...         queue input values
+l [addr] [n]     list n instructions from addr (default current position)
+o                show collected outputs
+save <file>      write a snapshot of the machine, outputs are not saved
+load <file>      replace the machine with a saved snapshot
+h                help
+q                quit";
+
+const OPCODES: [&str; 10] = [
+    "ADD", "MUL", "IN", "OUT", "JT", "JF", "LT", "EQ", "ARB", "HLT",
+];
+const MAX_CELLS: usize = 4096;
+
+struct Debugger {
+    programm: Programm,
+    addresses: BTreeSet<usize>,
+    opcodes: BTreeSet<String>,
+    outputs: Vec<i64>,
+    status: String,
+}
+
+impl Debugger {
+    fn new(programm: Programm) -> Self {
+        Debugger {
+            programm,
+            addresses: BTreeSet::new(),
+            opcodes: BTreeSet::new(),
+            outputs: vec![],
+            status: "ready".to_string(),
+        }
+    }
+
+    fn at_breakpoint(&self) -> bool {
+        self.addresses.contains(&self.programm.position())
+            || self
+                .programm
+                .current()
+                .map(|o| self.opcodes.contains(o.mnemonic()))
+                .unwrap_or(false)
+    }
+
+    fn step(&mut self) -> Result<StepOutcome, VmError> {
+        let outcome = self.programm.step()?;
+        if let StepOutcome::Output(value) = outcome {
+            self.outputs.push(value);
+            self.status = format!("output {}", value);
+        }
+        Ok(outcome)
+    }
+
+    fn execute(&mut self, count: Option<usize>) {
+        if count == Some(0) {
+            self.status = "stepped 0".to_string();
+            return;
+        }
+        let mut executed = 0;
+        loop {
+            match self.step() {
+                Ok(StepOutcome::NeedInput) => {
+                    self.status = "waiting for input, queue values with `i`".to_string();
+                    return;
+                }
+                Ok(StepOutcome::Halted) => {
+                    self.status = "halted".to_string();
+                    return;
+                }
+                Ok(StepOutcome::OutOfFuel) => {
+                    self.status = format!("out of fuel after {} steps", executed);
+                    return;
+                }
+                Ok(StepOutcome::LoopDetected) => {
+                    self.status = format!("loop detected at {}", self.programm.position());
+                    return;
+                }
+                Ok(StepOutcome::Watchpoint) => {
+                    self.status = self
+                        .programm
+                        .take_hits()
+                        .iter()
+                        .map(|h| h.to_string())
+                        .collect::<Vec<String>>()
+                        .join("; ");
+                    return;
+                }
+                Ok(_) => executed += 1,
+                Err(e) => {
+                    self.status = format!("error: {}", e);
+                    return;
+                }
+            }
+            match count {
+                Some(n) if executed >= n => {
+                    self.status = format!("stepped {}", executed);
+                    return;
+                }
+                None if self.at_breakpoint() => {
+                    self.status = format!("breakpoint at {}", self.programm.position());
+                    return;
+                }
+                _ => (),
+            }
+        }
+    }
+
+    fn toggle_breakpoint(&mut self, target: &str, set: bool) {
+        match target.parse::<usize>() {
+            Ok(address) if set => {
+                self.addresses.insert(address);
+            }
+            Ok(address) => {
+                self.addresses.remove(&address);
+            }
+            Err(_) => {
+                let name = target.to_uppercase();
+                if !OPCODES.contains(&name.as_str()) {
+                    println!(
+                        "unknown opcode `{}`, expected one of {}",
+                        target,
+                        OPCODES.join(", ")
+                    );
+                } else if set {
+                    self.opcodes.insert(name);
+                } else {
+                    self.opcodes.remove(&name);
+                }
+            }
+        }
+    }
+
+    fn list(&self, start: usize, count: usize) -> String {
+        disasm::window(&self.programm.image(), start, count)
+            .iter()
+            .map(|l| {
+                if l.address() == self.programm.position() {
+                    format!("{}{}{}\n", style::Bold, l, style::Reset)
+                } else {
+                    format!("{}\n", l)
+                }
+            })
+            .collect()
+    }
+
+    fn state(&self) -> String {
+        let current = match self.programm.current() {
+            Ok(opcode) => opcode.to_string(),
+            Err(e) => format!("{}", e),
+        };
+        format!(
+            "{}pc {} rb {} inputs {} | {}{}{}\n{}{}{}",
+            color::Fg(color::LightBlack),
+            self.programm.position(),
+            self.programm.relative_base(),
+            self.programm.pending_inputs(),
+            self.status,
+            color::Fg(color::Reset),
+            style::Bold,
+            color::Fg(color::Green),
+            current,
+            style::Reset,
+        )
+    }
+
+    fn command(&mut self, line: &str) -> bool {
+        let words = line.split_whitespace().collect::<Vec<&str>>();
+        let number = |i: usize| words.get(i).and_then(|w| w.parse::<i64>().ok());
+        let address = |i: usize| number(i).and_then(|n| usize::try_from(n).ok());
+        let count = |i: usize, default: usize| {
+            if words.len() > i {
+                address(i)
+            } else {
+                Some(default)
+            }
+        };
+        match words.first().cloned().unwrap_or("s") {
+            "s" => match count(1, 1) {
+                Some(n) => self.execute(Some(n)),
+                None => println!("s [n]"),
+            },
+            "c" => self.execute(None),
+            "b" | "d" if words.len() > 1 => self.toggle_breakpoint(words[1], words[0] == "b"),
+            "bl" => println!(
+                "addresses: {:?}\nopcodes: {:?}",
+                self.addresses, self.opcodes
+            ),
+            "watch" => {
+                let access = match words.get(1).cloned() {
+                    Some("r") => Some(Access::Read),
+                    Some("w") => Some(Access::Write),
+                    Some("p") => Some(Access::Patch),
+                    _ => None,
+                };
+                match (access, address(2), count(3, 0)) {
+                    (Some(access), Some(start), Some(end)) => {
+                        self.programm
+                            .watch(start..=end.max(start), access, WatchAction::Stop);
+                    }
+                    _ => println!("watch <r|w|p> <addr> [end]"),
+                }
+            }
+            "watches" => self
+                .programm
+                .watchpoints()
+                .iter()
+                .enumerate()
+                .for_each(|(i, w)| println!("{}: {:?} {:?}", i, w.access, w.range)),
+            "unwatch" => match address(1).and_then(|n| self.programm.unwatch(n)) {
+                Some(_) => (),
+                None => println!("unwatch <n>"),
+            },
+            "x" => match (address(1), count(2, 8)) {
+                (Some(_), Some(count)) if count > MAX_CELLS => {
+                    println!("x shows at most {} cells", MAX_CELLS)
+                }
+                (Some(address), Some(count)) => {
+                    let cells = (address..address.saturating_add(count))
+                        .map(|a| self.programm.read(a).to_string())
+                        .collect::<Vec<String>>();
+                    println!("{:>6}: {}", address, cells.join(" "));
+                }
+                _ => println!("x <addr> [n]"),
+            },
+            "w" => match (address(1), number(2)) {
+                (Some(address), Some(value)) => {
+                    if let Err(e) = self.programm.alter(address, value) {
+                        println!("{}", e);
+                    }
+                }
+                _ => println!("w <addr> <value>"),
+            },
+            "rb" => match number(1) {
+                Some(value) => self.programm.set_relative_base(value),
+                None => println!("{}", self.programm.relative_base()),
+            },
+            "i" => (1..words.len())
+                .filter_map(number)
+                .for_each(|v| self.programm.push_input(v)),
+            "l" => match (count(1, self.programm.position()), count(2, 10)) {
+                (Some(start), Some(count)) => print!("{}", self.list(start, count)),
+                _ => println!("l [addr] [n]"),
+            },
+            "o" => println!("{:?}", self.outputs),
+            "save" if words.len() > 1 => match self.programm.save(words[1]) {
+                Ok(()) => self.status = format!("saved {}", words[1]),
+                Err(e) => println!("{}", e),
+            },
+            "load" if words.len() > 1 => match Programm::load(words[1]) {
+                Ok(programm) => {
+                    self.programm = programm;
+                    self.outputs.clear();
+                    self.status = format!("loaded {}", words[1]);
+                }
+                Err(e) => println!("{}", e),
+            },
+            "h" => println!("{}", HELP),
+            "q" => return false,
+            _ => println!("Unknown command, `h` for help"),
+        }
+        true
+    }
+}
+
+pub fn run(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: debug <file>");
+            return;
+        }
+    };
+    let input = File::open(path).unwrap();
+    let mut input = BufReader::new(input);
+    let mut buffer = String::new();
+    input.read_to_string(&mut buffer).unwrap();
+
+    let mut debugger = Debugger::new(buffer.parse::<Programm>().unwrap());
+    let stdin = std::io::stdin();
+    let mut lines = stdin.lock().lines();
+    loop {
+        println!("{}", debugger.state());
+        print!("{}(icd){} ", color::Fg(color::Blue), color::Fg(color::Reset));
+        std::io::stdout().flush().unwrap();
+        match lines.next() {
+            Some(Ok(line)) => {
+                if !debugger.command(&line) {
+                    break;
+                }
+            }
+            _ => break,
+        }
+    }
+}
@@ -1,5 +1,7 @@
 use std::env;
 
+mod compiled;
+mod debugger;
 mod opcodes;
 
 mod task_1;
@@ -19,10 +21,13 @@ mod task_6;
 mod task_7;
 mod task_8;
 mod task_9;
+mod tools;
 
 fn main() {
     let arg = env::args().nth(1);
+    let rest = env::args().skip(2).collect::<Vec<String>>();
     match arg {
+        Some(x) if tools::is_tool(&x) => tools::run(&x, &rest),
         Some(x) => {
             println!("Task: {}", x);
             match x.as_str() {
@@ -0,0 +1,400 @@
+use super::disasm::reachable_from;
+use super::{IterInput, Mode, Opcode, Programm, StepOutcome, VmError};
+use std::fmt::Write;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Exit {
+    Halted,
+    NeedInput,
+}
+
+pub struct Machine {
+    programm: Programm,
+    image: &'static [i64],
+    modified: Vec<bool>,
+    changes: usize,
+    outputs: Vec<i64>,
+    pub pc: usize,
+}
+
+impl Machine {
+    pub fn new(image: &'static [i64]) -> Self {
+        Machine {
+            programm: Programm::from(image.to_vec()),
+            image,
+            modified: vec![false; image.len()],
+            changes: 0,
+            outputs: vec![],
+            pc: 0,
+        }
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.programm.push_input(value);
+    }
+
+    pub fn outputs(&self) -> &[i64] {
+        &self.outputs
+    }
+
+    pub fn programm(&self) -> &Programm {
+        &self.programm
+    }
+
+    pub fn clean(&self, address: usize, length: usize) -> bool {
+        self.changes == 0 || !self.modified[address..address + length].contains(&true)
+    }
+
+    pub fn get(&self, address: usize) -> i64 {
+        self.programm.code.get(address)
+    }
+
+    pub fn relative(&self, offset: i64) -> Result<usize, VmError> {
+        match self.programm.relative_base.checked_add(offset) {
+            Some(address) if address >= 0 => Ok(address as usize),
+            Some(value) => Err(VmError::NegativeAddress {
+                address: self.pc,
+                value,
+            }),
+            None => Err(VmError::AddressOverflow { address: self.pc }),
+        }
+    }
+
+    pub fn target(&self, value: i64) -> Result<usize, VmError> {
+        if value < 0 {
+            return Err(VmError::NegativeAddress {
+                address: self.pc,
+                value,
+            });
+        }
+        Ok(value as usize)
+    }
+
+    fn track(&mut self, address: usize, value: i64) {
+        if let Some(original) = self.image.get(address) {
+            let modified = value != *original;
+            if modified != self.modified[address] {
+                self.modified[address] = modified;
+                if modified {
+                    self.changes += 1;
+                } else {
+                    self.changes -= 1;
+                }
+            }
+        }
+    }
+
+    pub fn set(&mut self, address: usize, value: i64) -> Result<(), VmError> {
+        if !self.programm.code.set(address, value) {
+            return Err(VmError::MemoryLimit {
+                address: self.pc,
+                target: address,
+                limit: self.programm.code.limit(),
+            });
+        }
+        self.programm.invalidate(address);
+        self.track(address, value);
+        Ok(())
+    }
+
+    pub fn input(&mut self) -> Option<i64> {
+        let input = self.programm.inputs.pop_front();
+        if input.is_none() {
+            self.programm.position = self.pc;
+        }
+        input
+    }
+
+    pub fn output(&mut self, value: i64) {
+        self.outputs.push(value);
+    }
+
+    pub fn adjust(&mut self, value: i64) -> Result<(), VmError> {
+        self.programm.relative_base = self
+            .programm
+            .relative_base
+            .checked_add(value)
+            .ok_or(VmError::AddressOverflow { address: self.pc })?;
+        Ok(())
+    }
+
+    pub fn halt(&mut self) -> Exit {
+        self.programm.position = self.pc;
+        self.programm.is_finished = true;
+        Exit::Halted
+    }
+
+    pub fn interpret(&mut self) -> Result<Option<Exit>, VmError> {
+        self.programm.position = self.pc;
+        let written = match self.programm.current() {
+            Ok(Opcode::Add { to, .. })
+            | Ok(Opcode::Multiply { to, .. })
+            | Ok(Opcode::Less { to, .. })
+            | Ok(Opcode::Equal { to, .. })
+            | Ok(Opcode::Input { to }) => to.address(&self.programm).ok(),
+            _ => None,
+        };
+        let outcome = self.programm.step()?;
+        self.pc = self.programm.position;
+        if let Some(address) = written {
+            self.track(address, self.programm.code.get(address));
+        }
+        Ok(match outcome {
+            StepOutcome::Output(value) => {
+                self.outputs.push(value);
+                None
+            }
+            StepOutcome::NeedInput => Some(Exit::NeedInput),
+            StepOutcome::Halted => Some(Exit::Halted),
+            _ => None,
+        })
+    }
+}
+
+pub struct Compiled {
+    pub name: &'static str,
+    pub image: &'static [i64],
+    pub run: fn(&mut Machine) -> Result<Exit, VmError>,
+}
+
+impl Compiled {
+    pub fn execute(&self, inputs: &[i64]) -> (Result<Exit, VmError>, Machine) {
+        let mut machine = Machine::new(self.image);
+        for input in inputs {
+            machine.push_input(*input);
+        }
+        let result = (self.run)(&mut machine);
+        (result, machine)
+    }
+
+    pub fn verify(&self, inputs: &[i64]) -> Result<(), String> {
+        let mut programm = Programm::from(self.image.to_vec());
+        let mut expected = vec![];
+        let interpreted = programm
+            .run_with(&mut IterInput(inputs.iter().cloned()), &mut expected)
+            .map(|outcome| match outcome {
+                StepOutcome::Halted => Exit::Halted,
+                _ => Exit::NeedInput,
+            });
+        let (compiled, machine) = self.execute(inputs);
+        if compiled != interpreted {
+            return Err(format!(
+                "interpreter finished with {:?}, compiled code with {:?}",
+                interpreted, compiled
+            ));
+        }
+        if machine.outputs() != expected.as_slice() {
+            return Err(format!(
+                "interpreter printed {:?}, compiled code {:?}",
+                expected,
+                machine.outputs()
+            ));
+        }
+        let state = |p: &Programm| (p.position(), p.relative_base(), p.image());
+        if state(&programm) != state(machine.programm()) {
+            return Err("final position, relative base or memory differ".to_string());
+        }
+        Ok(())
+    }
+}
+
+fn value(mode: &Mode) -> String {
+    match mode {
+        Mode::Position(p) => format!("m.get({})", p),
+        Mode::Immediate(v) => v.to_string(),
+        Mode::Relative(o) => format!("m.get(m.relative({})?)", o),
+    }
+}
+
+fn store(out: &mut String, to: &Mode, expr: String) {
+    match to {
+        Mode::Position(p) => writeln!(out, "                m.set({}, {})?;", p, expr),
+        Mode::Relative(o) => writeln!(
+            out,
+            "                let v = {};\n                m.set(m.relative({})?, v)?;",
+            expr, o
+        ),
+        Mode::Immediate(_) => unreachable!(),
+    }
+    .unwrap();
+}
+
+fn arithmetic(left: &Mode, right: &Mode, method: &str, fold: fn(i64, i64) -> i64) -> String {
+    match (left, right) {
+        (Mode::Immediate(l), Mode::Immediate(r)) => fold(*l, *r).to_string(),
+        (Mode::Immediate(_), _) => format!("{}.{}({})", value(right), method, value(left)),
+        _ => format!("{}.{}({})", value(left), method, value(right)),
+    }
+}
+
+fn compare(left: &Mode, right: &Mode, operator: &str, fold: fn(&i64, &i64) -> bool) -> String {
+    match (left, right) {
+        (Mode::Immediate(l), Mode::Immediate(r)) => (fold(l, r) as i64).to_string(),
+        _ => format!("({} {} {}) as i64", value(left), operator, value(right)),
+    }
+}
+
+fn writes_immediate(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::Add {
+            to: Mode::Immediate(_),
+            ..
+        } | Opcode::Multiply {
+            to: Mode::Immediate(_),
+            ..
+        } | Opcode::Less {
+            to: Mode::Immediate(_),
+            ..
+        } | Opcode::Equal {
+            to: Mode::Immediate(_),
+            ..
+        } | Opcode::Input {
+            to: Mode::Immediate(_)
+        }
+    )
+}
+
+fn jump(out: &mut String, check: &Mode, to: &Mode, taken_if: &str, next: usize) {
+    let target = match to {
+        Mode::Immediate(t) if *t >= 0 => t.to_string(),
+        _ => format!("m.target({})?", value(to)),
+    };
+    let taken = match check {
+        Mode::Immediate(c) => Some((*c != 0) == (taken_if == "!=")),
+        _ => None,
+    };
+    match taken {
+        Some(true) => writeln!(out, "                m.pc = {};", target),
+        Some(false) => writeln!(out, "                m.pc = {};", next),
+        None => writeln!(
+            out,
+            "                m.pc = if {} {} 0 {{ {} }} else {{ {} }};",
+            value(check),
+            taken_if,
+            target,
+            next
+        ),
+    }
+    .unwrap();
+}
+
+fn arm(out: &mut String, address: usize, opcode: &Opcode) {
+    let next = address + opcode.length();
+    writeln!(
+        out,
+        "            {} if m.clean({}, {}) => {{",
+        address,
+        address,
+        opcode.length()
+    )
+    .unwrap();
+    let mut advance = true;
+    match opcode {
+        Opcode::Add { left, right, to } => store(
+            out,
+            to,
+            arithmetic(left, right, "wrapping_add", i64::wrapping_add),
+        ),
+        Opcode::Multiply { left, right, to } => store(
+            out,
+            to,
+            arithmetic(left, right, "wrapping_mul", i64::wrapping_mul),
+        ),
+        Opcode::Less { left, right, to } => store(out, to, compare(left, right, "<", i64::lt)),
+        Opcode::Equal { left, right, to } => store(out, to, compare(left, right, "==", i64::eq)),
+        Opcode::Input { to } => {
+            writeln!(
+                out,
+                "                let input = match m.input() {{\n                    Some(input) => input,\n                    None => return Ok(Exit::NeedInput),\n                }};"
+            )
+            .unwrap();
+            store(out, to, "input".to_string());
+        }
+        Opcode::Output { from } => {
+            writeln!(out, "                m.output({});", value(from)).unwrap()
+        }
+        Opcode::AdjustRelativeBase { to } => {
+            writeln!(out, "                m.adjust({})?;", value(to)).unwrap()
+        }
+        Opcode::JumpIfTrue { check, to } => {
+            jump(out, check, to, "!=", next);
+            advance = false;
+        }
+        Opcode::JumpIfFalse { check, to } => {
+            jump(out, check, to, "==", next);
+            advance = false;
+        }
+        Opcode::Halt => {
+            writeln!(out, "                return Ok(m.halt());").unwrap();
+            advance = false;
+        }
+    }
+    if advance {
+        writeln!(out, "                m.pc = {};", next).unwrap();
+    }
+    writeln!(out, "            }}").unwrap();
+}
+
+pub fn translate(name: &str, code: &[i64]) -> String {
+    let mut instructions = reachable_from(code, 0)
+        .into_iter()
+        .filter(|(_, opcode)| !writes_immediate(opcode))
+        .collect::<Vec<_>>();
+    instructions.sort_by_key(|(address, _)| *address);
+    let mut out = String::new();
+    writeln!(
+        out,
+        "// Generated by `aot` from a {}-cell Intcode image, do not edit.",
+        code.len()
+    )
+    .unwrap();
+    writeln!(out, "use crate::opcodes::aot::{{Compiled, Exit, Machine}};").unwrap();
+    writeln!(out, "use crate::opcodes::VmError;\n").unwrap();
+    writeln!(out, "pub static IMAGE: [i64; {}] = [", code.len()).unwrap();
+    for chunk in code.chunks(12) {
+        let cells = chunk
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        writeln!(out, "    {},", cells).unwrap();
+    }
+    writeln!(out, "];\n").unwrap();
+    writeln!(
+        out,
+        "pub static COMPILED: Compiled = Compiled {{\n    name: \"{}\",\n    image: &IMAGE,\n    run,\n}};\n",
+        name
+    )
+    .unwrap();
+    writeln!(out, "fn run(m: &mut Machine) -> Result<Exit, VmError> {{").unwrap();
+    writeln!(out, "    loop {{\n        match m.pc {{").unwrap();
+    for (address, opcode) in instructions.iter() {
+        arm(&mut out, *address, opcode);
+    }
+    writeln!(
+        out,
+        "            _ => {{\n                if let Some(exit) = m.interpret()? {{\n                    return Ok(exit);\n                }}\n            }}"
+    )
+    .unwrap();
+    writeln!(out, "        }}\n    }}\n}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn adjust_reports_overflow() {
+        static IMAGE: [i64; 5] = [109, i64::MAX, 109, 1, 99];
+        let mut machine = Machine::new(&IMAGE);
+        assert_eq!(machine.adjust(i64::MAX), Ok(()));
+        machine.pc = 2;
+        assert_eq!(
+            machine.adjust(1),
+            Err(VmError::AddressOverflow { address: 2 })
+        );
+        assert_eq!(machine.programm().relative_base(), i64::MAX);
+    }
+}
@@ -0,0 +1,223 @@
+use super::{Mode, Programm, VmError};
+use num::bigint::{BigInt, Sign};
+use num::{ToPrimitive, Zero};
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Default)]
+pub enum Arithmetic {
+    #[default]
+    Wrapping,
+    Checked,
+    Big,
+}
+
+impl Arithmetic {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Arithmetic::Wrapping => "wrapping",
+            Arithmetic::Checked => "checked",
+            Arithmetic::Big => "big",
+        }
+    }
+}
+
+impl FromStr for Arithmetic {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "wrapping" => Ok(Arithmetic::Wrapping),
+            "checked" => Ok(Arithmetic::Checked),
+            "big" => Ok(Arithmetic::Big),
+            _ => Err(format!("unknown arithmetic `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct BigCells {
+    pub(super) cells: HashMap<usize, BigInt>,
+    outputs: Vec<BigInt>,
+}
+
+impl BigCells {
+    pub(super) fn contains(&self, address: usize) -> bool {
+        !self.cells.is_empty() && self.cells.contains_key(&address)
+    }
+
+    pub(super) fn remove(&mut self, address: usize) {
+        if !self.cells.is_empty() {
+            self.cells.remove(&address);
+        }
+    }
+}
+
+pub(super) fn low_bits(value: &BigInt) -> i64 {
+    let (sign, bytes) = value.to_bytes_le();
+    let mut low = [0_u8; 8];
+    for (i, b) in bytes.iter().take(8).enumerate() {
+        low[i] = *b;
+    }
+    let bits = u64::from_le_bytes(low) as i64;
+    if sign == Sign::Minus {
+        bits.wrapping_neg()
+    } else {
+        bits
+    }
+}
+
+impl Mode {
+    fn get_big(&self, v: &mut Programm) -> Result<BigInt, VmError> {
+        let exact = match self {
+            Mode::Immediate(_) => None,
+            _ => {
+                let address = self.address(v)?;
+                v.big.cells.get(&address).cloned()
+            }
+        };
+        match exact {
+            Some(value) => {
+                v.traced(|t| t.operand(low_bits(&value)));
+                Ok(value)
+            }
+            None => self.get(v).map(BigInt::from),
+        }
+    }
+
+    fn set_big(&self, v: &mut Programm, value: BigInt) -> Result<(), VmError> {
+        match value.to_i64() {
+            Some(small) => self.set(v, small),
+            None => {
+                self.set(v, low_bits(&value))?;
+                let address = self.address(v)?;
+                v.big.cells.insert(address, value);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Programm {
+    pub fn with_arithmetic(mut self, arithmetic: Arithmetic) -> Self {
+        self.arithmetic = arithmetic;
+        self
+    }
+
+    pub fn take_big_outputs(&mut self) -> Vec<BigInt> {
+        std::mem::take(&mut self.big.outputs)
+    }
+
+    pub(super) fn combine(
+        &mut self,
+        (left, right, to): (&Mode, &Mode, &Mode),
+        checked: fn(i64, i64) -> Option<i64>,
+        wrapping: fn(i64, i64) -> i64,
+        big: fn(BigInt, BigInt) -> BigInt,
+    ) -> Result<(), VmError> {
+        if self.arithmetic == Arithmetic::Big {
+            let l = left.get_big(self)?;
+            let r = right.get_big(self)?;
+            return to.set_big(self, big(l, r));
+        }
+        let l = left.get(self)?;
+        let r = right.get(self)?;
+        let value = match self.arithmetic {
+            Arithmetic::Checked => checked(l, r).ok_or(VmError::ArithmeticOverflow {
+                address: self.position,
+            })?,
+            _ => wrapping(l, r),
+        };
+        to.set(self, value)
+    }
+
+    pub(super) fn compare(
+        &mut self,
+        (left, right, to): (&Mode, &Mode, &Mode),
+        small: fn(&i64, &i64) -> bool,
+        big: fn(&BigInt, &BigInt) -> bool,
+    ) -> Result<(), VmError> {
+        let result = if self.arithmetic == Arithmetic::Big && !self.big.cells.is_empty() {
+            big(&left.get_big(self)?, &right.get_big(self)?)
+        } else {
+            small(&left.get(self)?, &right.get(self)?)
+        };
+        to.set(self, if result { 1 } else { 0 })
+    }
+
+    pub(super) fn is_nonzero(&mut self, check: &Mode) -> Result<bool, VmError> {
+        if self.arithmetic == Arithmetic::Big && !self.big.cells.is_empty() {
+            Ok(!check.get_big(self)?.is_zero())
+        } else {
+            Ok(check.get(self)? != 0)
+        }
+    }
+
+    pub(super) fn output_value(&mut self, from: &Mode) -> Result<i64, VmError> {
+        if self.arithmetic == Arithmetic::Big {
+            let value = from.get_big(self)?;
+            let output = low_bits(&value);
+            self.big.outputs.push(value);
+            Ok(output)
+        } else {
+            from.get(self)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // [19] = MAX * 2, [20] = [19] - MAX, [21] = [19] < MAX, then prints all three
+    const DOUBLED: &str = "1102,9223372036854775807,2,19,\
+                           1001,19,-9223372036854775807,20,\
+                           1007,19,9223372036854775807,21,\
+                           4,19,4,20,4,21,99,0,0,0";
+
+    fn run(arithmetic: Arithmetic) -> (Result<Vec<i64>, VmError>, Programm) {
+        let mut programm = DOUBLED
+            .parse::<Programm>()
+            .unwrap()
+            .with_arithmetic(arithmetic);
+        (programm.run(&mut vec![]), programm)
+    }
+
+    #[test]
+    fn wrapping_wraps_around() {
+        assert_eq!(run(Arithmetic::Wrapping).0, Ok(vec![-2, i64::MAX, 1]));
+    }
+
+    #[test]
+    fn checked_reports_overflow() {
+        assert_eq!(
+            run(Arithmetic::Checked).0,
+            Err(VmError::ArithmeticOverflow { address: 0 })
+        );
+    }
+
+    #[test]
+    fn big_keeps_exact_values() {
+        let (outputs, mut programm) = run(Arithmetic::Big);
+        assert_eq!(outputs, Ok(vec![-2, i64::MAX, 0]));
+        let doubled = BigInt::from(i64::MAX) * BigInt::from(2);
+        assert_eq!(
+            programm.take_big_outputs(),
+            vec![doubled, BigInt::from(i64::MAX), BigInt::zero()]
+        );
+    }
+
+    #[test]
+    fn big_values_are_not_jump_targets() {
+        let mut programm = "1102,9223372036854775807,2,8,105,1,8,99,0"
+            .parse::<Programm>()
+            .unwrap()
+            .with_arithmetic(Arithmetic::Big);
+        assert_eq!(
+            programm.run(&mut vec![]),
+            Err(VmError::ValueOutOfRange {
+                address: 4,
+                target: 8
+            })
+        );
+    }
+}
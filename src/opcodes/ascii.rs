@@ -0,0 +1,135 @@
+use super::{Programm, StepOutcome, VmError};
+use std::io::BufRead;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Output {
+    Line(String),
+    Value(i64),
+}
+
+pub fn text(outputs: &[Output]) -> String {
+    outputs
+        .iter()
+        .filter_map(|o| match o {
+            Output::Line(line) => Some(format!("{}\n", line)),
+            Output::Value(_) => None,
+        })
+        .collect()
+}
+
+pub struct Ascii {
+    programm: Programm,
+}
+
+impl Ascii {
+    pub fn new(programm: Programm) -> Self {
+        Ascii { programm }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.programm.is_finished()
+    }
+
+    pub fn send_line(&mut self, line: &str) {
+        for c in line.chars() {
+            self.programm.push_input(c as i64);
+        }
+        self.programm.push_input('\n' as i64);
+    }
+
+    pub fn run(&mut self) -> Result<Vec<Output>, VmError> {
+        let mut result = vec![];
+        let mut line = String::new();
+        self.programm.run_with(&mut || None, &mut |value: i64| {
+            match value {
+                10 => result.push(Output::Line(std::mem::take(&mut line))),
+                0..=127 => line.push(value as u8 as char),
+                _ => {
+                    if !line.is_empty() {
+                        result.push(Output::Line(std::mem::take(&mut line)));
+                    }
+                    result.push(Output::Value(value));
+                }
+            }
+        })?;
+        if !line.is_empty() {
+            result.push(Output::Line(line));
+        }
+        Ok(result)
+    }
+
+    pub fn interactive<R: BufRead>(&mut self, input: R) -> Result<StepOutcome, VmError> {
+        let mut lines = input.lines();
+        loop {
+            for output in self.run()? {
+                match output {
+                    Output::Line(line) => println!("{}", line),
+                    Output::Value(value) => println!("{}", value),
+                }
+            }
+            if self.is_finished() {
+                return Ok(StepOutcome::Halted);
+            }
+            match lines.next() {
+                Some(Ok(line)) => self.send_line(&line),
+                _ => return Ok(StepOutcome::NeedInput),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Echoes every character up to and including a newline.
+    const ECHO: &str = "3,100,4,100,1008,100,10,101,1006,101,0,99";
+
+    fn ascii(code: &str) -> Ascii {
+        Ascii::new(code.parse().unwrap())
+    }
+
+    #[test]
+    fn sends_lines_with_a_newline() {
+        let mut echo = ascii(ECHO);
+        assert_eq!(echo.run(), Ok(vec![]));
+        echo.send_line("hi there");
+        assert_eq!(echo.run(), Ok(vec![Output::Line("hi there".to_string())]));
+        assert!(echo.is_finished());
+    }
+
+    #[test]
+    fn values_above_127_split_lines() {
+        let mut ascii = ascii("104,72,104,105,104,128,104,33,104,-1,104,10,99");
+        assert_eq!(
+            ascii.run(),
+            Ok(vec![
+                Output::Line("Hi".to_string()),
+                Output::Value(128),
+                Output::Line("!".to_string()),
+                Output::Value(-1),
+                Output::Line("".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn non_ascii_input_comes_back_as_values() {
+        let mut echo = ascii(ECHO);
+        echo.send_line("é");
+        assert_eq!(
+            echo.run(),
+            Ok(vec![Output::Value(233), Output::Line("".to_string())])
+        );
+    }
+
+    #[test]
+    fn text_keeps_only_lines() {
+        let outputs = vec![
+            Output::Line("a".to_string()),
+            Output::Value(1000),
+            Output::Line("b".to_string()),
+        ];
+        assert_eq!(text(&outputs), "a\nb\n");
+    }
+}
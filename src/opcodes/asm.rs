@@ -0,0 +1,281 @@
+use super::Programm;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AsmError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl AsmError {
+    fn new(line: usize, message: String) -> Self {
+        AsmError { line, message }
+    }
+}
+
+impl Display for AsmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for AsmError {}
+
+const MNEMONICS: [(&str, &str, i64, usize); 10] = [
+    ("add", "add", 1, 3),
+    ("mul", "multiply", 2, 3),
+    ("in", "input", 3, 1),
+    ("out", "output", 4, 1),
+    ("jt", "jumpiftrue", 5, 2),
+    ("jf", "jumpiffalse", 6, 2),
+    ("lt", "less", 7, 3),
+    ("eq", "equal", 8, 3),
+    ("arb", "adjustrelativebase", 9, 1),
+    ("hlt", "halt", 99, 0),
+];
+
+enum Value {
+    Number(i64),
+    Label(String, i64),
+}
+
+enum Item {
+    Instruction { code: i64, operands: Vec<(i64, Value)> },
+    Data(Vec<Value>),
+}
+
+impl Item {
+    fn length(&self) -> usize {
+        match self {
+            Item::Instruction { operands, .. } => operands.len() + 1,
+            Item::Data(values) => values.len(),
+        }
+    }
+}
+
+fn is_label(s: &str) -> bool {
+    let mut chars = s.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => {
+            chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        _ => false,
+    }
+}
+
+fn parse_value(s: &str) -> Result<Value, String> {
+    let s = s.trim();
+    if let Ok(v) = s.parse::<i64>() {
+        return Ok(Value::Number(v));
+    }
+    let (label, offset) = match s.rfind(['+', '-']) {
+        Some(i) if i > 0 => {
+            let offset = s[i + 1..]
+                .trim()
+                .parse::<i64>()
+                .map_err(|_| format!("bad offset in `{}`", s))?;
+            let offset = if &s[i..=i] == "-" { -offset } else { offset };
+            (s[..i].trim(), offset)
+        }
+        _ => (s, 0),
+    };
+    if is_label(label) {
+        Ok(Value::Label(label.to_string(), offset))
+    } else {
+        Err(format!("bad value `{}`", s))
+    }
+}
+
+fn parse_operand(s: &str) -> Result<(i64, Value), String> {
+    let s = s.trim();
+    if let Some(v) = s.strip_prefix('#') {
+        Ok((1, parse_value(v)?))
+    } else if let Some(v) = s.strip_prefix("rb[").and_then(|v| v.strip_suffix(']')) {
+        Ok((2, parse_value(v)?))
+    } else if let Some(v) = s.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+        Ok((0, parse_value(v)?))
+    } else {
+        Err(format!("operand `{}` needs a mode: [x], #x or rb[x]", s))
+    }
+}
+
+fn parse_item(mnemonic: &str, rest: &str) -> Result<Item, String> {
+    let lower = mnemonic.to_lowercase();
+    if lower == "data" {
+        return rest
+            .split(',')
+            .map(parse_value)
+            .collect::<Result<Vec<Value>, String>>()
+            .map(Item::Data);
+    }
+    let (_, _, code, count) = MNEMONICS
+        .iter()
+        .find(|(short, long, _, _)| *short == lower || *long == lower)
+        .ok_or(format!("unknown mnemonic `{}`", mnemonic))?;
+    let operands = rest
+        .replace("->", ",")
+        .split(',')
+        .filter(|o| !o.trim().is_empty())
+        .map(parse_operand)
+        .collect::<Result<Vec<(i64, Value)>, String>>()?;
+    if operands.len() != *count {
+        return Err(format!(
+            "`{}` takes {} operands, got {}",
+            mnemonic,
+            count,
+            operands.len()
+        ));
+    }
+    Ok(Item::Instruction {
+        code: *code,
+        operands,
+    })
+}
+
+fn resolve(value: &Value, labels: &HashMap<String, usize>) -> Result<i64, String> {
+    match value {
+        Value::Number(v) => Ok(*v),
+        Value::Label(name, offset) => labels
+            .get(name)
+            .map(|a| *a as i64 + offset)
+            .ok_or(format!("unknown label `{}`", name)),
+    }
+}
+
+pub fn assemble(source: &str) -> Result<Vec<i64>, AsmError> {
+    let mut labels = HashMap::new();
+    let mut items = vec![];
+    let mut address = 0;
+    for (n, line) in source.lines().enumerate() {
+        let n = n + 1;
+        let mut rest = line.split(';').next().unwrap().trim();
+        while let Some(i) = rest.find(':') {
+            let name = rest[..i].trim();
+            if let Ok(expected) = name.parse::<usize>() {
+                if expected != address {
+                    return Err(AsmError::new(
+                        n,
+                        format!("address {} does not match {}", expected, address),
+                    ));
+                }
+            } else if !is_label(name) {
+                return Err(AsmError::new(n, format!("bad label `{}`", name)));
+            } else if labels.insert(name.to_string(), address).is_some() {
+                return Err(AsmError::new(n, format!("duplicate label `{}`", name)));
+            }
+            rest = rest[i + 1..].trim();
+        }
+        if rest.is_empty() {
+            continue;
+        }
+        let (mnemonic, operands) = match rest.find(char::is_whitespace) {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, ""),
+        };
+        let item = parse_item(mnemonic, operands).map_err(|e| AsmError::new(n, e))?;
+        address += item.length();
+        items.push((n, item));
+    }
+
+    let mut result = Vec::with_capacity(address);
+    for (n, item) in items {
+        let resolve = |v: &Value| resolve(v, &labels).map_err(|e| AsmError::new(n, e));
+        match item {
+            Item::Instruction { code, operands } => {
+                let modes = operands
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, (mode, _)| acc * 10 + mode);
+                result.push(code + modes * 100);
+                for (_, value) in operands.iter() {
+                    result.push(resolve(value)?);
+                }
+            }
+            Item::Data(values) => {
+                for value in values.iter() {
+                    result.push(resolve(value)?);
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub fn to_text(code: &[i64]) -> String {
+    code.iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+impl Programm {
+    pub fn assemble(source: &str) -> Result<Programm, AsmError> {
+        assemble(source).map(Programm::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::disasm::{linear, listing};
+    use crate::opcodes::StepOutcome;
+
+    const COUNTDOWN: &str = "
+        ; counts down from the input to zero
+        start:  in -> [counter]
+        loop:   out [counter]          ; print before decrementing
+                add [counter], #-1 -> [counter]
+                jt [counter], #loop
+                hlt
+        counter: data 0
+    ";
+
+    fn error(source: &str) -> AsmError {
+        assemble(source).unwrap_err()
+    }
+
+    #[test]
+    fn resolves_labels_and_skips_comments() {
+        assert_eq!(
+            assemble(COUNTDOWN),
+            Ok(vec![3, 12, 4, 12, 1001, 12, -1, 12, 1005, 12, 2, 99, 0])
+        );
+    }
+
+    #[test]
+    fn labels_take_offsets_and_long_names() {
+        let code = assemble("jumpiftrue #1, #end+1\nend: data end-1, 7\nhalt").unwrap();
+        assert_eq!(code, vec![1105, 1, 4, 2, 7, 99]);
+    }
+
+    #[test]
+    fn runs_what_it_assembles() {
+        let mut programm = Programm::assemble(COUNTDOWN).unwrap();
+        programm.push_input(3);
+        let mut outputs = vec![];
+        while let Ok(StepOutcome::Output(v)) = programm.run_until(|o| o != &StepOutcome::Continued)
+        {
+            outputs.push(v);
+        }
+        assert_eq!(outputs, vec![3, 2, 1]);
+    }
+
+    #[test]
+    fn listing_of_assembled_code_reassembles() {
+        let code = assemble(COUNTDOWN).unwrap();
+        assert_eq!(assemble(&listing(&linear(&code))), Ok(code));
+    }
+
+    #[test]
+    fn errors_carry_their_line() {
+        assert_eq!(error("hlt\nfoo [1]").line, 2);
+        assert!(error("hlt\nfoo [1]").message.contains("unknown mnemonic"));
+        assert_eq!(error("\n\nadd [1], 2 -> [3]").line, 3);
+        assert!(error("out [1], [2]").message.contains("takes 1 operands"));
+        assert!(error("jt #1, #nowhere").message.contains("unknown label"));
+        assert!(error("a: hlt\na: hlt").message.contains("duplicate label"));
+        assert!(error("hlt\n0: hlt").message.contains("does not match"));
+        assert!(error("data 1, 2x").message.contains("bad value"));
+    }
+}
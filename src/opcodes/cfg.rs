@@ -0,0 +1,252 @@
+use super::disasm::{reachable_from, successors};
+use super::profile::Profile;
+use super::{Mode, Opcode};
+use std::collections::{BTreeMap, BTreeSet};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum EdgeKind {
+    Fallthrough,
+    Jump,
+    Resolved(u64),
+    Unresolved,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Edge {
+    pub from: usize,
+    pub to: Option<usize>,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone)]
+pub struct Block {
+    pub start: usize,
+    pub instructions: Vec<(usize, Opcode)>,
+}
+
+impl Block {
+    fn last(&self) -> (usize, Opcode) {
+        *self.instructions.last().unwrap()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Cfg {
+    pub blocks: Vec<Block>,
+    pub edges: Vec<Edge>,
+}
+
+fn indirect(opcode: &Opcode) -> bool {
+    match opcode {
+        Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => {
+            let jumps_if_true = matches!(opcode, Opcode::JumpIfTrue { .. });
+            let may_jump = match check {
+                Mode::Immediate(c) => (*c != 0) == jumps_if_true,
+                _ => true,
+            };
+            may_jump && !matches!(to, Mode::Immediate(t) if *t >= 0)
+        }
+        _ => false,
+    }
+}
+
+fn is_jump(opcode: &Opcode) -> bool {
+    matches!(
+        opcode,
+        Opcode::JumpIfTrue { .. } | Opcode::JumpIfFalse { .. } | Opcode::Halt
+    )
+}
+
+pub fn build(code: &[i64], profile: Option<&Profile>) -> Cfg {
+    let mut found = reachable_from(code, 0)
+        .into_iter()
+        .collect::<BTreeMap<usize, Opcode>>();
+    let mut resolved = vec![];
+    if let Some(profile) = profile {
+        for ((from, to), count) in profile.jumps() {
+            if found.get(&from).is_some_and(indirect) {
+                resolved.push((from, to, count));
+                for (address, opcode) in reachable_from(code, to) {
+                    found.entry(address).or_insert(opcode);
+                }
+            }
+        }
+    }
+
+    let mut leaders = BTreeSet::new();
+    leaders.insert(0);
+    for (address, opcode) in found.iter() {
+        if is_jump(opcode) {
+            leaders.extend(successors(opcode, *address));
+            leaders.insert(address + opcode.length());
+        }
+    }
+    leaders.extend(resolved.iter().map(|(_, to, _)| *to));
+
+    let mut blocks: Vec<Block> = vec![];
+    let mut end = None;
+    for (address, opcode) in found.iter() {
+        match blocks.last_mut() {
+            Some(block) if end == Some(*address) && !leaders.contains(address) => {
+                block.instructions.push((*address, *opcode))
+            }
+            _ => blocks.push(Block {
+                start: *address,
+                instructions: vec![(*address, *opcode)],
+            }),
+        }
+        end = if is_jump(opcode) {
+            None
+        } else {
+            Some(address + opcode.length())
+        };
+    }
+
+    let starts = blocks.iter().map(|b| b.start).collect::<BTreeSet<usize>>();
+    let mut edges = vec![];
+    for block in blocks.iter() {
+        let (address, opcode) = block.last();
+        let next = address + opcode.length();
+        for target in successors(&opcode, address) {
+            if starts.contains(&target) {
+                edges.push(Edge {
+                    from: block.start,
+                    to: Some(target),
+                    kind: if is_jump(&opcode) && target != next {
+                        EdgeKind::Jump
+                    } else {
+                        EdgeKind::Fallthrough
+                    },
+                });
+            }
+        }
+        if indirect(&opcode) {
+            let targets = resolved
+                .iter()
+                .filter(|(from, to, _)| *from == address && starts.contains(to))
+                .collect::<Vec<_>>();
+            if targets.is_empty() {
+                edges.push(Edge {
+                    from: block.start,
+                    to: None,
+                    kind: EdgeKind::Unresolved,
+                });
+            }
+            for (_, to, count) in targets {
+                edges.push(Edge {
+                    from: block.start,
+                    to: Some(*to),
+                    kind: EdgeKind::Resolved(*count),
+                });
+            }
+        }
+    }
+    Cfg { blocks, edges }
+}
+
+impl Cfg {
+    pub fn to_dot(&self) -> String {
+        let mut result = String::from("digraph cfg {\n");
+        result.push_str("    node [shape=box, fontname=\"monospace\"];\n");
+        for block in self.blocks.iter() {
+            let label = block
+                .instructions
+                .iter()
+                .map(|(address, opcode)| format!("{}: {}\\l", address, opcode))
+                .collect::<String>()
+                .replace('"', "\\\"");
+            result.push_str(&format!("    b{} [label=\"{}\"];\n", block.start, label));
+        }
+        for edge in self.edges.iter() {
+            let line = match (edge.to, edge.kind) {
+                (None, _) => format!(
+                    "    u{0} [label=\"?\", shape=circle];\n    b{0} -> u{0} [style=dashed];\n",
+                    edge.from
+                ),
+                (Some(to), EdgeKind::Resolved(count)) => format!(
+                    "    b{} -> b{} [style=dotted, label=\"{}\"];\n",
+                    edge.from, to, count
+                ),
+                (Some(to), EdgeKind::Jump) => {
+                    format!("    b{} -> b{} [label=\"jump\"];\n", edge.from, to)
+                }
+                (Some(to), _) => format!("    b{} -> b{};\n", edge.from, to),
+            };
+            result.push_str(&line);
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::Programm;
+
+    // Reads a flag and jumps through [13] to print 7 when it is set.
+    const COMPUTED: [i64; 14] = [3, 12, 5, 12, 13, 99, 104, 7, 99, 0, 0, 0, 0, 6];
+
+    fn starts(cfg: &Cfg) -> Vec<usize> {
+        cfg.blocks.iter().map(|b| b.start).collect()
+    }
+
+    fn edge(from: usize, to: Option<usize>, kind: EdgeKind) -> Edge {
+        Edge { from, to, kind }
+    }
+
+    #[test]
+    fn splits_blocks_after_conditional_jumps() {
+        let cfg = build(&[1001, 8, -1, 8, 1005, 8, 0, 99, 3], None);
+        assert_eq!(starts(&cfg), vec![0, 7]);
+        assert_eq!(cfg.blocks[0].instructions.len(), 2);
+        assert_eq!(
+            cfg.edges,
+            vec![
+                edge(0, Some(0), EdgeKind::Jump),
+                edge(0, Some(7), EdgeKind::Fallthrough),
+            ]
+        );
+    }
+
+    #[test]
+    fn constant_conditions_keep_one_edge() {
+        let cfg = build(&[1106, 0, 4, 99, 99], None);
+        assert_eq!(starts(&cfg), vec![0, 4]);
+        assert_eq!(cfg.edges, vec![edge(0, Some(4), EdgeKind::Jump)]);
+    }
+
+    #[test]
+    fn computed_jumps_are_unresolved_without_a_profile() {
+        let cfg = build(&COMPUTED, None);
+        assert_eq!(starts(&cfg), vec![0, 5]);
+        assert_eq!(
+            cfg.edges,
+            vec![
+                edge(0, Some(5), EdgeKind::Fallthrough),
+                edge(0, None, EdgeKind::Unresolved),
+            ]
+        );
+        assert!(cfg.to_dot().contains("b0 -> u0 [style=dashed]"));
+    }
+
+    #[test]
+    fn profiles_resolve_computed_jumps() {
+        let mut programm = Programm::from(COMPUTED.to_vec());
+        programm.start_profile();
+        assert_eq!(programm.run(&mut vec![1]), Ok(vec![7]));
+        let cfg = build(&COMPUTED, programm.profile());
+        assert_eq!(starts(&cfg), vec![0, 5, 6]);
+        assert_eq!(cfg.blocks[2].instructions.len(), 2);
+        assert_eq!(
+            cfg.edges,
+            vec![
+                edge(0, Some(5), EdgeKind::Fallthrough),
+                edge(0, Some(6), EdgeKind::Resolved(1)),
+            ]
+        );
+        assert!(cfg
+            .to_dot()
+            .contains("b0 -> b6 [style=dotted, label=\"1\"]"));
+    }
+}
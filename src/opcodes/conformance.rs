@@ -0,0 +1,397 @@
+use super::{Programm, StepOutcome, VmError};
+use Source::{Intcode, Lang};
+
+pub enum Expect {
+    Outputs(&'static [i64]),
+    Memory(&'static [i64]),
+    Spins(usize),
+    Rejected { index: usize, offset: usize },
+    Fails(VmError),
+}
+
+pub enum Source {
+    Intcode(&'static str),
+    Lang(&'static str),
+}
+
+pub struct Case {
+    pub name: &'static str,
+    pub code: Source,
+    pub inputs: &'static [i64],
+    pub expect: Expect,
+}
+
+const FUEL: u64 = 10_000;
+
+pub fn cases() -> Vec<Case> {
+    vec![
+        Case {
+            name: "day 2 add and multiply",
+            code: Intcode("1,9,10,3,2,3,11,0,99,30,40,50"),
+            inputs: &[],
+            expect: Expect::Memory(&[3500, 9, 10, 70, 2, 3, 11, 0, 99, 30, 40, 50]),
+        },
+        Case {
+            name: "day 2 patches the next instruction",
+            code: Intcode("1,1,1,4,99,5,6,0,99"),
+            inputs: &[],
+            expect: Expect::Memory(&[30, 1, 1, 4, 2, 5, 6, 0, 99]),
+        },
+        Case {
+            name: "day 5 equal to 8 in position mode",
+            code: Intcode("3,9,8,9,10,9,4,9,99,-1,8"),
+            inputs: &[8],
+            expect: Expect::Outputs(&[1]),
+        },
+        Case {
+            name: "day 5 less than 8 in immediate mode",
+            code: Intcode("3,3,1107,-1,8,3,4,3,99"),
+            inputs: &[9],
+            expect: Expect::Outputs(&[0]),
+        },
+        Case {
+            name: "day 5 jumps in position mode",
+            code: Intcode("3,12,6,12,15,1,13,14,13,4,13,99,-1,0,1,9"),
+            inputs: &[0],
+            expect: Expect::Outputs(&[0]),
+        },
+        Case {
+            name: "day 9 quine",
+            code: Intcode("109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99"),
+            inputs: &[],
+            expect: Expect::Outputs(&[
+                109, 1, 204, -1, 1001, 100, 1, 100, 1008, 100, 16, 101, 1006, 101, 0, 99,
+            ]),
+        },
+        Case {
+            name: "day 9 large multiplication",
+            code: Intcode("1102,34915192,34915192,7,4,7,99,0"),
+            inputs: &[],
+            expect: Expect::Outputs(&[1_219_070_632_396_864]),
+        },
+        Case {
+            name: "overwrites own opcode with a different value",
+            code: Intcode("1101,1,1,0,99"),
+            inputs: &[],
+            expect: Expect::Memory(&[2, 1, 1, 0, 99]),
+        },
+        Case {
+            name: "overwrites own opcode with the same value",
+            code: Intcode("1101,1100,1,0,99"),
+            inputs: &[],
+            expect: Expect::Memory(&[1101, 1100, 1, 0, 99]),
+        },
+        Case {
+            name: "overwrites own destination operand",
+            code: Intcode("1101,5,0,3,99"),
+            inputs: &[],
+            expect: Expect::Memory(&[1101, 5, 0, 5, 99]),
+        },
+        Case {
+            name: "input patches the following output",
+            code: Intcode("3,3,104,0,99"),
+            inputs: &[42],
+            expect: Expect::Outputs(&[42]),
+        },
+        Case {
+            name: "patches a jump into a halt",
+            code: Intcode("1101,99,0,4,1105,1,0"),
+            inputs: &[],
+            expect: Expect::Memory(&[1101, 99, 0, 4, 99, 1, 0]),
+        },
+        Case {
+            name: "jump to self not taken",
+            code: Intcode("1105,0,0,104,7,99"),
+            inputs: &[],
+            expect: Expect::Outputs(&[7]),
+        },
+        Case {
+            name: "jump to self taken",
+            code: Intcode("104,1,1105,1,2,99"),
+            inputs: &[],
+            expect: Expect::Spins(2),
+        },
+        Case {
+            name: "jump to an identical instruction",
+            code: Intcode("1106,0,3,1106,0,6,104,3,99"),
+            inputs: &[],
+            expect: Expect::Outputs(&[3]),
+        },
+        Case {
+            name: "relative base overflow is an error",
+            code: Intcode("109,9223372036854775807,109,1,99"),
+            inputs: &[],
+            expect: Expect::Fails(VmError::AddressOverflow { address: 2 }),
+        },
+        Case {
+            name: "negative relative address is an error",
+            code: Intcode("109,-5,204,1,99"),
+            inputs: &[],
+            expect: Expect::Fails(VmError::NegativeAddress {
+                address: 2,
+                value: -4,
+            }),
+        },
+        Case {
+            name: "parses whitespace, newlines and comments",
+            code: Intcode(
+                "# reads a value and prints it doubled
+                3, 9,          # in -> [9]
+                1002, 9, 2, 9, # mul [9], #2 -> [9]
+                4, 9,
+                99,
+                0
+                ",
+            ),
+            inputs: &[21],
+            expect: Expect::Outputs(&[42]),
+        },
+        Case {
+            name: "rejects a typo instead of shifting cells",
+            code: Intcode("1,0,O,3,99"),
+            inputs: &[],
+            expect: Expect::Rejected {
+                index: 2,
+                offset: 4,
+            },
+        },
+        Case {
+            name: "rejects an empty cell",
+            code: Intcode("1,0,0,,3,99"),
+            inputs: &[],
+            expect: Expect::Rejected {
+                index: 3,
+                offset: 6,
+            },
+        },
+        Case {
+            name: "rejects a trailing comma",
+            code: Intcode("104,1,99,\n"),
+            inputs: &[],
+            expect: Expect::Rejected {
+                index: 3,
+                offset: 9,
+            },
+        },
+        Case {
+            name: "lang echoes until zero",
+            code: Lang(
+                "fn main() {
+                    let x = input();
+                    while x != 0 {
+                        output(x);
+                        x = input();
+                    }
+                }",
+            ),
+            inputs: &[3, -4, 5, 0],
+            expect: Expect::Outputs(&[3, -4, 5]),
+        },
+        Case {
+            name: "lang arithmetic and precedence",
+            code: Lang(
+                "fn main() {
+                    let a = input();
+                    let b = input();
+                    output(a + b * 2 - (a - b) * 3);
+                    output(-a * -b);
+                    output(a < b && b < 10 || a == 7);
+                    output(!(a >= b) + (a <= b) + (a > b) + (a != b));
+                }",
+            ),
+            inputs: &[4, 6],
+            expect: Expect::Outputs(&[22, 24, 1, 3]),
+        },
+        Case {
+            name: "lang if and else if chains",
+            code: Lang(
+                "fn sign(x) {
+                    if x < 0 {
+                        return -1;
+                    } else if x == 0 {
+                        return 0;
+                    } else {
+                        return 1;
+                    }
+                }
+                fn main() {
+                    output(sign(input()));
+                    output(sign(input()));
+                    output(sign(input()));
+                }",
+            ),
+            inputs: &[-9, 0, 12],
+            expect: Expect::Outputs(&[-1, 0, 1]),
+        },
+        Case {
+            name: "lang recursive factorial",
+            code: Lang(
+                "fn factorial(n) {
+                    if n <= 1 {
+                        return 1;
+                    }
+                    return n * factorial(n - 1);
+                }
+                fn main() {
+                    output(factorial(input()));
+                }",
+            ),
+            inputs: &[10],
+            expect: Expect::Outputs(&[3_628_800]),
+        },
+        Case {
+            name: "lang nested calls keep their arguments",
+            code: Lang(
+                "fn add3(a, b, c) {
+                    return a + b + c;
+                }
+                fn square(x) {
+                    return x * x;
+                }
+                fn main() {
+                    output(add3(square(2), add3(1, square(3), 1), square(add3(1, 1, 1))));
+                }",
+            ),
+            inputs: &[],
+            expect: Expect::Outputs(&[24]),
+        },
+        Case {
+            name: "lang fibonacci with locals in loops",
+            code: Lang(
+                "// prints the first n fibonacci numbers
+                fn main() {
+                    let n = input();
+                    let a = 0;
+                    let b = 1;
+                    while n > 0 {
+                        output(a);
+                        let next = a + b;
+                        a = b;
+                        b = next;
+                        n = n - 1;
+                    }
+                }",
+            ),
+            inputs: &[8],
+            expect: Expect::Outputs(&[0, 1, 1, 2, 3, 5, 8, 13]),
+        },
+        Case {
+            name: "lang greatest common divisor",
+            code: Lang(
+                "fn gcd(a, b) {
+                    while a != b {
+                        if a > b {
+                            a = a - b;
+                        } else {
+                            b = b - a;
+                        }
+                    }
+                    return a;
+                }
+                fn main() {
+                    output(gcd(input(), input()));
+                }",
+            ),
+            inputs: &[84, 36],
+            expect: Expect::Outputs(&[12]),
+        },
+        Case {
+            name: "lang && and || skip the right operand",
+            code: Lang(
+                "fn seen(x) {
+                    output(x);
+                    return 1;
+                }
+                fn main() {
+                    let a = 0;
+                    if a != 0 && seen(10) {
+                        output(-1);
+                    }
+                    if a == 0 || seen(20) {
+                        output(2);
+                    }
+                    let b = input();
+                    output(b && seen(30));
+                    output(b || seen(40));
+                    output(a || b - 7);
+                    output(0 && seen(50));
+                    output(5 && b);
+                }",
+            ),
+            inputs: &[7],
+            expect: Expect::Outputs(&[2, 30, 1, 1, 0, 0, 1]),
+        },
+    ]
+}
+
+pub fn check(case: &Case) -> Result<(), String> {
+    let programm = match (&case.code, &case.expect) {
+        (Intcode(code), Expect::Rejected { index, offset }) => {
+            return match code.parse::<Programm>() {
+                Err(e) if e.index == *index && e.offset == *offset => Ok(()),
+                Err(e) => Err(format!("rejected with {}", e)),
+                Ok(_) => Err("expected a parse error".to_string()),
+            };
+        }
+        (Intcode(code), _) => code.parse::<Programm>().map_err(|e| e.to_string())?,
+        (Lang(source), _) => Programm::compile(source).map_err(|e| e.to_string())?,
+    };
+    let mut programm = programm.with_fuel(FUEL);
+    if let Expect::Spins(_) = case.expect {
+        programm = programm.with_loop_detection();
+    }
+    for input in case.inputs {
+        programm.push_input(*input);
+    }
+    let mut outputs = vec![];
+    loop {
+        let outcome = match (programm.step(), &case.expect) {
+            (Err(e), Expect::Fails(expected)) if e == *expected => return Ok(()),
+            (result, _) => result.map_err(|e| e.to_string())?,
+        };
+        match outcome {
+            StepOutcome::Output(value) => outputs.push(value),
+            StepOutcome::NeedInput => return Err("ran out of input".to_string()),
+            StepOutcome::OutOfFuel => return Err(format!("ran out of {} steps", FUEL)),
+            StepOutcome::Halted | StepOutcome::LoopDetected => break,
+            StepOutcome::Continued | StepOutcome::Watchpoint => (),
+        }
+    }
+    match case.expect {
+        Expect::Outputs(expected) if outputs != expected => Err(format!(
+            "expected outputs {:?}, got {:?}",
+            expected, outputs
+        )),
+        Expect::Memory(expected) if programm.image().get(..expected.len()) != Some(expected) => {
+            Err(format!(
+                "expected memory {:?}, got {:?}",
+                expected,
+                programm.image()
+            ))
+        }
+        Expect::Spins(address) if programm.is_finished() || programm.position() != address => {
+            Err(format!(
+                "expected to spin at {}, stopped at {}",
+                address,
+                programm.position()
+            ))
+        }
+        Expect::Rejected { .. } => Err("expected a parse error".to_string()),
+        Expect::Fails(ref expected) => Err(format!("expected to fail with {}", expected)),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_case_passes() {
+        let failures = cases()
+            .iter()
+            .filter_map(|case| check(case).err().map(|e| format!("{}: {}", case.name, e)))
+            .collect::<Vec<String>>();
+        assert!(failures.is_empty(), "{}", failures.join("\n"));
+    }
+}
@@ -0,0 +1,654 @@
+use super::disasm::reachable_from;
+use super::{Mode, Opcode};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Loc {
+    Mem(usize),
+    Frame(i64),
+    Rb(i64),
+}
+
+struct Function {
+    entry: usize,
+    body: BTreeMap<usize, Opcode>,
+    frames: HashMap<usize, Option<i64>>,
+}
+
+struct Carry {
+    loc: Loc,
+    expr: String,
+    start: usize,
+    compare: Option<(String, &'static str, String)>,
+}
+
+struct Loop {
+    header: usize,
+    end: usize,
+}
+
+struct Emitter<'a> {
+    function: &'a Function,
+    instructions: &'a BTreeMap<usize, Opcode>,
+    calls: &'a BTreeSet<usize>,
+    targets: BTreeSet<usize>,
+    lines: Vec<(usize, String)>,
+    gotos: BTreeSet<usize>,
+}
+
+fn immediate(mode: &Mode) -> Option<i64> {
+    match mode {
+        Mode::Immediate(v) => Some(*v),
+        _ => None,
+    }
+}
+
+fn unconditional(opcode: &Opcode) -> Option<&Mode> {
+    match opcode {
+        Opcode::JumpIfTrue { check, to } if immediate(check).is_some_and(|c| c != 0) => Some(to),
+        Opcode::JumpIfFalse { check, to } if immediate(check) == Some(0) => Some(to),
+        _ => None,
+    }
+}
+
+fn static_jump(opcode: &Opcode) -> Option<usize> {
+    match opcode {
+        Opcode::JumpIfTrue { to, .. } | Opcode::JumpIfFalse { to, .. } => {
+            immediate(to).filter(|t| *t >= 0).map(|t| t as usize)
+        }
+        _ => None,
+    }
+}
+
+fn destination(opcode: &Opcode) -> Option<&Mode> {
+    match opcode {
+        Opcode::Add { to, .. }
+        | Opcode::Multiply { to, .. }
+        | Opcode::Less { to, .. }
+        | Opcode::Equal { to, .. }
+        | Opcode::Input { to } => Some(to),
+        _ => None,
+    }
+}
+
+fn sources(opcode: &Opcode) -> Vec<&Mode> {
+    match opcode {
+        Opcode::Add { left, right, .. }
+        | Opcode::Multiply { left, right, .. }
+        | Opcode::Less { left, right, .. }
+        | Opcode::Equal { left, right, .. } => vec![left, right],
+        Opcode::Output { from } => vec![from],
+        Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => vec![check, to],
+        Opcode::AdjustRelativeBase { to } => vec![to],
+        _ => vec![],
+    }
+}
+
+fn constant(opcode: &Opcode) -> Option<i64> {
+    match opcode {
+        Opcode::Add { left, right, .. } => immediate(left)?.checked_add(immediate(right)?),
+        Opcode::Multiply { left, right, .. } => immediate(left)?.checked_mul(immediate(right)?),
+        _ => None,
+    }
+}
+
+fn is_call(body: &BTreeMap<usize, Opcode>, address: usize, opcode: &Opcode) -> bool {
+    let return_site = (address + opcode.length()) as i64;
+    unconditional(opcode).is_some_and(|to| immediate(to).is_some())
+        && body
+            .range(..address)
+            .next_back()
+            .is_some_and(|(a, previous)| {
+                a + previous.length() == address && constant(previous) == Some(return_site)
+            })
+}
+
+fn function(code: &[i64], entry: usize, all: &BTreeMap<usize, Opcode>) -> Function {
+    let mut body = BTreeMap::new();
+    let mut frames = HashMap::new();
+    let mut queue: Vec<(usize, Option<i64>)> = vec![(entry, Some(0))];
+    while let Some((address, frame)) = queue.pop() {
+        if body.contains_key(&address) {
+            if frames.get(&address) != Some(&frame) {
+                frames.insert(address, None);
+            }
+            continue;
+        }
+        let opcode = match all.get(&address) {
+            Some(opcode) => *opcode,
+            None => match Opcode::from_vec(code, address) {
+                Ok(opcode) => opcode,
+                Err(_) => continue,
+            },
+        };
+        body.insert(address, opcode);
+        frames.insert(address, frame);
+        let next = address + opcode.length();
+        let frame = match opcode {
+            Opcode::AdjustRelativeBase { to } => frame
+                .zip(immediate(&to))
+                .and_then(|(f, n)| f.checked_add(n)),
+            _ => frame,
+        };
+        if is_call(&body, address, &opcode) {
+            queue.push((next, frame));
+            continue;
+        }
+        match opcode {
+            Opcode::Halt => (),
+            Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => {
+                if unconditional(&opcode).is_none() || immediate(&check).is_none() {
+                    queue.push((next, frame));
+                }
+                if let Some(t) = immediate(&to).filter(|t| *t >= 0) {
+                    queue.push((t as usize, frame));
+                }
+            }
+            _ => queue.push((next, frame)),
+        }
+    }
+    Function {
+        entry,
+        body,
+        frames,
+    }
+}
+
+fn inverse(op: &str) -> &'static str {
+    match op {
+        "<" => ">=",
+        ">=" => "<",
+        "==" => "!=",
+        _ => "==",
+    }
+}
+
+fn wrap(s: String) -> String {
+    if s.contains(' ') {
+        format!("({})", s)
+    } else {
+        s
+    }
+}
+
+impl<'a> Emitter<'a> {
+    fn loc(&self, address: usize, mode: &Mode) -> Option<Loc> {
+        match mode {
+            Mode::Position(p) => Some(Loc::Mem(*p)),
+            Mode::Relative(o) => {
+                let frame = self.function.frames.get(&address).cloned().flatten();
+                Some(
+                    frame
+                        .and_then(|f| f.checked_add(*o))
+                        .map_or(Loc::Rb(*o), Loc::Frame),
+                )
+            }
+            Mode::Immediate(_) => None,
+        }
+    }
+
+    fn operand(&self, address: usize, mode: &Mode, carry: &Option<Carry>) -> String {
+        match (self.loc(address, mode), carry) {
+            (Some(loc), Some(c)) if loc == c.loc => wrap(c.expr.clone()),
+            (Some(Loc::Mem(p)), _) => format!("mem[{}]", p),
+            (Some(Loc::Frame(s)), _) => format!("frame[{}]", s),
+            (Some(Loc::Rb(o)), _) => format!("rb[{}]", o),
+            (None, _) => immediate(mode).unwrap().to_string(),
+        }
+    }
+
+    fn comparison(
+        &self,
+        address: usize,
+        opcode: &Opcode,
+        carry: &Option<Carry>,
+    ) -> Option<(String, &'static str, String)> {
+        let op = |m: &Mode| self.operand(address, m, carry);
+        match opcode {
+            Opcode::Less { left, right, .. } => Some((op(left), "<", op(right))),
+            Opcode::Equal { left, right, .. } => Some((op(left), "==", op(right))),
+            _ => None,
+        }
+    }
+
+    fn expression(&self, address: usize, opcode: &Opcode, carry: &Option<Carry>) -> Option<String> {
+        let op = |m: &Mode| self.operand(address, m, carry);
+        Some(match opcode {
+            _ if constant(opcode).is_some() => constant(opcode).unwrap().to_string(),
+            Opcode::Add { left, right, .. } => match (immediate(left), immediate(right)) {
+                (Some(0), _) => op(right),
+                (_, Some(0)) => op(left),
+                (_, Some(r)) if r < 0 => format!("{} - {}", op(left), r.unsigned_abs()),
+                _ => format!("{} + {}", op(left), op(right)),
+            },
+            Opcode::Multiply { left, right, .. } => match (immediate(left), immediate(right)) {
+                (Some(0), _) | (_, Some(0)) => "0".to_string(),
+                (Some(1), _) => op(right),
+                (_, Some(1)) => op(left),
+                (Some(-1), _) => format!("-{}", op(right)),
+                (_, Some(-1)) => format!("-{}", op(left)),
+                _ => format!("{} * {}", op(left), op(right)),
+            },
+            Opcode::Input { .. } => "input()".to_string(),
+            _ => {
+                let (l, op, r) = self.comparison(address, opcode, carry)?;
+                format!("{} {} {}", l, op, r)
+            }
+        })
+    }
+
+    fn condition(
+        &self,
+        address: usize,
+        opcode: &Opcode,
+        carry: &Option<Carry>,
+        taken: bool,
+    ) -> String {
+        let (check, nonzero) = match opcode {
+            Opcode::JumpIfTrue { check, .. } => (check, taken),
+            Opcode::JumpIfFalse { check, .. } => (check, !taken),
+            _ => return "true".to_string(),
+        };
+        let compare = match (self.loc(address, check), carry) {
+            (Some(loc), Some(c)) if loc == c.loc => c.compare.clone(),
+            _ => None,
+        };
+        match compare {
+            Some((l, op, r)) if nonzero => format!("{} {} {}", l, op, r),
+            Some((l, op, r)) => format!("{} {} {}", l, inverse(op), r),
+            None if nonzero => format!("{} != 0", self.operand(address, check, carry)),
+            None => format!("{} == 0", self.operand(address, check, carry)),
+        }
+    }
+
+    fn folds(&self, address: usize, opcode: &Opcode) -> Option<Loc> {
+        let loc = self.loc(address, destination(opcode)?)?;
+        let next = address + opcode.length();
+        if self.targets.contains(&next) {
+            return None;
+        }
+        let following = self.function.body.get(&next)?;
+        let reads = sources(following)
+            .into_iter()
+            .filter(|m| self.loc(next, m) == Some(loc))
+            .count();
+        let overwritten = destination(following).and_then(|m| self.loc(next, m)) == Some(loc);
+        let checked = matches!(following, Opcode::JumpIfTrue { check, .. } | Opcode::JumpIfFalse { check, .. }
+            if self.loc(next, check) == Some(loc));
+        if reads == 1 && (overwritten || checked) {
+            Some(loc)
+        } else {
+            None
+        }
+    }
+
+    fn push(&mut self, address: usize, depth: usize, text: String) {
+        self.lines
+            .push((address, format!("{}{}", "    ".repeat(depth), text)));
+    }
+
+    fn close(&mut self, depth: usize, text: &str) {
+        self.push(usize::MAX, depth, text.to_string());
+    }
+
+    fn jump(&mut self, target: usize, loops: &[Loop]) -> String {
+        match loops.last() {
+            Some(l) if l.header == target => "continue;".to_string(),
+            Some(l) if l.end == target => "break;".to_string(),
+            _ => {
+                self.gotos.insert(target);
+                format!("goto l_{};", target)
+            }
+        }
+    }
+
+    fn latch(&self, header: usize, to: usize) -> Option<usize> {
+        self.function
+            .body
+            .range(header..to)
+            .filter(|(a, o)| static_jump(o) == Some(header) && !self.is_call(**a, o))
+            .map(|(a, _)| *a)
+            .next_back()
+    }
+
+    fn emit(
+        &mut self,
+        from: usize,
+        to: usize,
+        depth: usize,
+        loops: &mut Vec<Loop>,
+        skip_loop: Option<usize>,
+    ) {
+        let mut address = from;
+        let mut carry: Option<Carry> = None;
+        while let Some((&a, &opcode)) = self.function.body.range(address..to.max(address)).next() {
+            if skip_loop != Some(a) {
+                if let Some(latch) = self.latch(a, to) {
+                    let end = latch + self.function.body[&latch].length();
+                    self.push(a, depth, "loop {".to_string());
+                    loops.push(Loop { header: a, end });
+                    self.emit(a, end, depth + 1, loops, Some(a));
+                    loops.pop();
+                    self.close(depth, "}");
+                    address = end;
+                    carry = None;
+                    continue;
+                }
+            }
+            let next = a + opcode.length();
+            address = next;
+            let incoming = carry.take();
+            let at = incoming.as_ref().map_or(a, |c| c.start);
+            if let Some(loc) = self.folds(a, &opcode) {
+                if let Some(expr) = self.expression(a, &opcode, &incoming) {
+                    carry = Some(Carry {
+                        loc,
+                        expr,
+                        start: at,
+                        compare: self.comparison(a, &opcode, &incoming),
+                    });
+                    continue;
+                }
+            }
+            match opcode {
+                Opcode::JumpIfTrue { to: target, .. } | Opcode::JumpIfFalse { to: target, .. } => {
+                    if self.is_call(a, &opcode) {
+                        self.push(at, depth, format!("f_{}();", immediate(&target).unwrap()));
+                        continue;
+                    }
+                    let always = unconditional(&opcode).is_some();
+                    let latch = loops.last().filter(|l| l.end == next).map(|l| l.header);
+                    match static_jump(&opcode) {
+                        Some(t) if latch == Some(t) => {
+                            if !always {
+                                let condition = self.condition(a, &opcode, &incoming, false);
+                                self.push(at, depth, format!("if {} {{ break; }}", condition));
+                            }
+                        }
+                        Some(t)
+                            if !always
+                                && t >= next
+                                && t <= to
+                                && !loops.iter().any(|l| l.end == t) =>
+                        {
+                            let condition = self.condition(a, &opcode, &incoming, false);
+                            let before = self.function.body.range(next..t).next_back();
+                            let otherwise = before.and_then(|(b, o)| {
+                                let u = static_jump(o).filter(|_| unconditional(o).is_some())?;
+                                (b + o.length() == t && u > t && u <= to && !self.is_call(*b, o))
+                                    .then_some((*b, u))
+                            });
+                            self.push(at, depth, format!("if {} {{", condition));
+                            match otherwise {
+                                Some((b, u)) => {
+                                    self.emit(next, b, depth + 1, loops, None);
+                                    self.close(depth, "} else {");
+                                    self.emit(t, u, depth + 1, loops, None);
+                                    self.close(depth, "}");
+                                    address = u;
+                                }
+                                None => {
+                                    self.emit(next, t, depth + 1, loops, None);
+                                    self.close(depth, "}");
+                                    address = t;
+                                }
+                            }
+                        }
+                        Some(t) if always && t == next => (),
+                        Some(t) => {
+                            let statement = self.jump(t, loops);
+                            if always {
+                                self.push(at, depth, statement);
+                            } else {
+                                let condition = self.condition(a, &opcode, &incoming, true);
+                                self.push(
+                                    at,
+                                    depth,
+                                    format!("if {} {{ {} }}", condition, statement),
+                                );
+                            }
+                        }
+                        None if always => match target {
+                            Mode::Relative(_) => self.push(at, depth, "return;".to_string()),
+                            _ => {
+                                let t = self.operand(a, &target, &incoming);
+                                self.push(at, depth, format!("goto *{};", t));
+                            }
+                        },
+                        None => {
+                            let condition = self.condition(a, &opcode, &incoming, true);
+                            let t = self.operand(a, &target, &incoming);
+                            self.push(at, depth, format!("if {} {{ goto *{}; }}", condition, t));
+                        }
+                    }
+                }
+                Opcode::Output { from } => {
+                    let value = self.operand(a, &from, &incoming);
+                    self.push(at, depth, format!("output({});", value));
+                }
+                Opcode::AdjustRelativeBase { to: offset } => {
+                    let value = self.operand(a, &offset, &incoming);
+                    self.push(at, depth, format!("rb += {};", value));
+                }
+                Opcode::Halt => self.push(at, depth, "halt();".to_string()),
+                _ => {
+                    let to = destination(&opcode).unwrap();
+                    let target = self.operand(a, to, &None);
+                    let expr = self.expression(a, &opcode, &incoming).unwrap();
+                    let patched = match self.loc(a, to) {
+                        Some(Loc::Mem(p)) => self
+                            .instructions
+                            .range(..=p)
+                            .next_back()
+                            .filter(|(i, o)| *i + o.length() > p)
+                            .map(|(i, _)| format!(" // patches instruction at {}", i)),
+                        _ => None,
+                    };
+                    self.push(
+                        at,
+                        depth,
+                        format!("{} = {};{}", target, expr, patched.unwrap_or_default()),
+                    );
+                }
+            }
+        }
+    }
+
+    fn is_call(&self, address: usize, opcode: &Opcode) -> bool {
+        static_jump(opcode).is_some_and(|t| self.calls.contains(&t))
+            && is_call(&self.function.body, address, opcode)
+    }
+
+    fn render(mut self) -> String {
+        let entry = self.function.entry;
+        let end = self
+            .function
+            .body
+            .keys()
+            .next_back()
+            .map_or(entry, |a| a + 1);
+        let start = *self.function.body.keys().next().unwrap_or(&entry);
+        if entry != start {
+            self.gotos.insert(entry);
+            self.lines.push((start, format!("    goto l_{};", entry)));
+        }
+        self.emit(start, end, 1, &mut vec![], None);
+        // Jumps into an instruction or out of the body have no line to label.
+        let starts = self
+            .lines
+            .iter()
+            .map(|(a, _)| *a)
+            .collect::<BTreeSet<usize>>();
+        for target in self.gotos.difference(&starts) {
+            let label = format!("goto l_{};", target);
+            for (_, line) in self.lines.iter_mut().filter(|(_, l)| l.contains(&label)) {
+                *line = format!(
+                    "{} // no statement starts at {}",
+                    line.replace(&label, &format!("goto *{};", target)),
+                    target
+                );
+            }
+        }
+        let mut result = format!("fn f_{}() {{\n", entry);
+        let mut labelled = BTreeSet::new();
+        for (address, line) in self.lines.iter() {
+            if self.gotos.contains(address) && labelled.insert(*address) {
+                result.push_str(&format!("  l_{}:\n", address));
+            }
+            result.push_str(line);
+            result.push('\n');
+        }
+        result.push_str("}\n");
+        result
+    }
+}
+
+pub fn decompile(code: &[i64]) -> String {
+    let all = reachable_from(code, 0)
+        .into_iter()
+        .collect::<BTreeMap<usize, Opcode>>();
+    let mut calls = BTreeSet::new();
+    for (address, opcode) in all.iter() {
+        if is_call(&all, *address, opcode) {
+            calls.extend(static_jump(opcode));
+        }
+    }
+    let mut targets = BTreeSet::new();
+    for (address, opcode) in all.iter() {
+        targets.extend(static_jump(opcode));
+        if calls.contains(&static_jump(opcode).unwrap_or(usize::MAX)) {
+            targets.insert(address + opcode.length());
+        }
+    }
+    let entries = std::iter::once(0).chain(calls.iter().cloned().filter(|c| *c != 0));
+    entries
+        .map(|entry| {
+            let function = function(code, entry, &all);
+            let mut targets = targets.clone();
+            targets.extend(function.body.values().filter_map(static_jump));
+            let emitter = Emitter {
+                function: &function,
+                instructions: &all,
+                calls: &calls,
+                targets,
+                lines: vec![],
+                gotos: BTreeSet::new(),
+            };
+            emitter.render()
+        })
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::Programm;
+    use std::fs;
+
+    fn body(text: &str) -> Vec<&str> {
+        let lines = text.lines().collect::<Vec<&str>>();
+        lines[1..lines.len() - 1].to_vec()
+    }
+
+    #[test]
+    fn recovers_if_and_else() {
+        let text = decompile(&[3, 20, 1006, 20, 7, 104, 1, 99]);
+        assert_eq!(
+            body(&text),
+            vec![
+                "    if input() != 0 {",
+                "        output(1);",
+                "    }",
+                "    halt();"
+            ]
+        );
+        let text = decompile(&[3, 20, 1005, 20, 10, 104, 1, 1105, 1, 12, 104, 2, 99]);
+        assert_eq!(
+            body(&text),
+            vec![
+                "    if input() == 0 {",
+                "        output(1);",
+                "    } else {",
+                "        output(2);",
+                "    }",
+                "    halt();",
+            ]
+        );
+    }
+
+    #[test]
+    fn recovers_loops() {
+        let text = decompile(&[3, 20, 1001, 20, -1, 20, 4, 20, 1005, 20, 2, 99]);
+        assert_eq!(
+            body(&text),
+            vec![
+                "    mem[20] = input();",
+                "    loop {",
+                "        mem[20] = mem[20] - 1;",
+                "        output(mem[20]);",
+                "        if mem[20] == 0 { break; }",
+                "    }",
+                "    halt();",
+            ]
+        );
+    }
+
+    #[test]
+    fn tracks_frames_through_constant_adjustments() {
+        let text = decompile(&[109, 10, 22101, 1, 1, 2, 204, 2, 109, -3, 204, 0, 99]);
+        assert_eq!(
+            body(&text),
+            vec![
+                "    rb += 10;",
+                "    frame[12] = 1 + frame[11];",
+                "    output(frame[12]);",
+                "    rb += -3;",
+                "    output(frame[7]);",
+                "    halt();",
+            ]
+        );
+        let text = decompile(&[9, 20, 204, 0, 99]);
+        assert_eq!(body(&text)[1], "    output(rb[0]);");
+    }
+
+    #[test]
+    fn overflowing_frames_become_unknown() {
+        let text = decompile(&[109, 9223372036854775807, 109, 1, 204, 0, 99]);
+        assert_eq!(body(&text)[2], "    output(rb[0]);");
+        let text = decompile(&[109, 9223372036854775807, 1201, 1, 0, 0, 99]);
+        assert_eq!(
+            body(&text)[1],
+            "    mem[0] = rb[1]; // patches instruction at 0"
+        );
+    }
+
+    #[test]
+    fn jumps_without_a_statement_stay_raw() {
+        let text = decompile(&[1101, 104, 7, 20, 3, 21, 1005, 21, 1, 99]);
+        assert_eq!(
+            body(&text),
+            vec![
+                "    mem[20] = 111;",
+                "    if input() != 0 { goto *1; } // no statement starts at 1",
+                "    halt();",
+            ]
+        );
+        let image = fs::read_to_string("input/task_11")
+            .unwrap()
+            .parse::<Programm>()
+            .unwrap()
+            .image();
+        let text = decompile(&image);
+        let labels = text
+            .lines()
+            .filter_map(|l| l.trim().strip_prefix("l_")?.strip_suffix(':'))
+            .collect::<BTreeSet<&str>>();
+        for line in text.lines() {
+            if let Some(i) = line.find("goto l_") {
+                let label = line[i + 7..].split(';').next().unwrap();
+                assert!(labels.contains(label), "{}", line);
+            }
+        }
+    }
+}
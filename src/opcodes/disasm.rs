@@ -0,0 +1,369 @@
+use super::{Mode, Opcode, Programm};
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+impl Display for Mode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Mode::Position(p) => write!(f, "[{}]", p),
+            Mode::Immediate(v) => write!(f, "#{}", v),
+            Mode::Relative(p) => write!(f, "rb[{}]", p),
+        }
+    }
+}
+
+impl Opcode {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            Opcode::Add { .. } => "ADD",
+            Opcode::Multiply { .. } => "MUL",
+            Opcode::Halt => "HLT",
+            Opcode::Input { .. } => "IN",
+            Opcode::Output { .. } => "OUT",
+            Opcode::JumpIfTrue { .. } => "JT",
+            Opcode::JumpIfFalse { .. } => "JF",
+            Opcode::Less { .. } => "LT",
+            Opcode::Equal { .. } => "EQ",
+            Opcode::AdjustRelativeBase { .. } => "ARB",
+        }
+    }
+
+    pub fn encoded(&self) -> i64 {
+        let (code, operands) = match self {
+            Opcode::Add { left, right, to } => (1, vec![left, right, to]),
+            Opcode::Multiply { left, right, to } => (2, vec![left, right, to]),
+            Opcode::Input { to } => (3, vec![to]),
+            Opcode::Output { from } => (4, vec![from]),
+            Opcode::JumpIfTrue { check, to } => (5, vec![check, to]),
+            Opcode::JumpIfFalse { check, to } => (6, vec![check, to]),
+            Opcode::Less { left, right, to } => (7, vec![left, right, to]),
+            Opcode::Equal { left, right, to } => (8, vec![left, right, to]),
+            Opcode::AdjustRelativeBase { to } => (9, vec![to]),
+            Opcode::Halt => (99, vec![]),
+        };
+        let modes = operands.iter().rev().fold(0, |acc, mode| {
+            acc * 10
+                + match mode {
+                    Mode::Position(_) => 0,
+                    Mode::Immediate(_) => 1,
+                    Mode::Relative(_) => 2,
+                }
+        });
+        code + modes * 100
+    }
+}
+
+impl Display for Opcode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = self.mnemonic();
+        match self {
+            Opcode::Add { left, right, to }
+            | Opcode::Multiply { left, right, to }
+            | Opcode::Less { left, right, to }
+            | Opcode::Equal { left, right, to } => {
+                write!(f, "{} {}, {} -> {}", name, left, right, to)
+            }
+            Opcode::Input { to } => write!(f, "{} -> {}", name, to),
+            Opcode::Output { from: to } | Opcode::AdjustRelativeBase { to } => {
+                write!(f, "{} {}", name, to)
+            }
+            Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => {
+                write!(f, "{} {}, {}", name, check, to)
+            }
+            Opcode::Halt => write!(f, "{}", name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Line {
+    Instruction {
+        address: usize,
+        raw: Vec<i64>,
+        opcode: Opcode,
+    },
+    Data {
+        address: usize,
+        value: i64,
+    },
+}
+
+impl Line {
+    pub fn address(&self) -> usize {
+        match self {
+            Line::Instruction { address, .. } | Line::Data { address, .. } => *address,
+        }
+    }
+
+    fn instruction(code: &[i64], address: usize, opcode: Opcode) -> Self {
+        Line::Instruction {
+            address,
+            raw: code[address..address + opcode.length()].to_vec(),
+            opcode,
+        }
+    }
+}
+
+impl Display for Line {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Line::Instruction {
+                address,
+                raw,
+                opcode,
+            } => {
+                let canonical = raw[0] == opcode.encoded();
+                let raw = raw
+                    .iter()
+                    .map(|v| v.to_string())
+                    .collect::<Vec<String>>()
+                    .join(",");
+                if canonical {
+                    write!(f, "{:>6}: {:<32} ; {}", address, opcode.to_string(), raw)
+                } else {
+                    write!(
+                        f,
+                        "{:>6}: {:<32} ; {}",
+                        address,
+                        format!("DATA {}", raw),
+                        opcode
+                    )
+                }
+            }
+            Line::Data { address, value } => write!(f, "{:>6}: DATA {}", address, value),
+        }
+    }
+}
+
+pub fn linear(code: &[i64]) -> Vec<Line> {
+    window(code, 0, code.len())
+}
+
+pub fn window(code: &[i64], start: usize, count: usize) -> Vec<Line> {
+    let mut result = vec![];
+    let mut address = start;
+    while address < code.len() && result.len() < count {
+        match Opcode::from_vec(code, address) {
+            Ok(opcode) => {
+                let length = opcode.length();
+                result.push(Line::instruction(code, address, opcode));
+                address += length;
+            }
+            Err(_) => {
+                result.push(Line::Data {
+                    address,
+                    value: code[address],
+                });
+                address += 1;
+            }
+        }
+    }
+    result
+}
+
+fn static_target(to: &Mode) -> Option<usize> {
+    match to {
+        Mode::Immediate(t) if *t >= 0 => Some(*t as usize),
+        _ => None,
+    }
+}
+
+pub fn successors(opcode: &Opcode, address: usize) -> Vec<usize> {
+    let next = address + opcode.length();
+    match opcode {
+        Opcode::Halt => vec![],
+        Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => {
+            let jumps_if_true = matches!(opcode, Opcode::JumpIfTrue { .. });
+            let (may_jump, may_fall) = match check {
+                Mode::Immediate(c) => ((*c != 0) == jumps_if_true, (*c != 0) != jumps_if_true),
+                _ => (true, true),
+            };
+            let mut result = vec![];
+            if may_jump {
+                result.extend(static_target(to));
+            }
+            if may_fall {
+                result.push(next);
+            }
+            result
+        }
+        _ => vec![next],
+    }
+}
+
+fn pushed_constants(opcode: &Opcode) -> Vec<usize> {
+    match opcode {
+        Opcode::Add { left, right, .. } | Opcode::Multiply { left, right, .. } => vec![left, right]
+            .into_iter()
+            .filter_map(static_target)
+            .collect(),
+        _ => vec![],
+    }
+}
+
+pub fn reachable_from(code: &[i64], entry: usize) -> HashMap<usize, Opcode> {
+    let mut found = HashMap::new();
+    let mut visited = HashSet::new();
+    let mut constants = HashSet::new();
+    let mut return_sites = HashSet::new();
+    let mut queue = vec![entry];
+    loop {
+        while let Some(address) = queue.pop() {
+            if address >= code.len() || !visited.insert(address) {
+                continue;
+            }
+            if let Ok(opcode) = Opcode::from_vec(code, address) {
+                let next = successors(&opcode, address);
+                let end = address + opcode.length();
+                if !next.contains(&end) && opcode != Opcode::Halt {
+                    return_sites.insert(end);
+                }
+                constants.extend(pushed_constants(&opcode));
+                queue.extend(next);
+                found.insert(address, opcode);
+            }
+        }
+        queue.extend(
+            return_sites
+                .intersection(&constants)
+                .filter(|a| !visited.contains(a)),
+        );
+        if queue.is_empty() {
+            return found;
+        }
+    }
+}
+
+pub fn reachable(code: &[i64]) -> Vec<Line> {
+    let mut found = reachable_from(code, 0);
+    let mut result = vec![];
+    let mut address = 0;
+    while address < code.len() {
+        if let Some(opcode) = found.remove(&address) {
+            let length = opcode.length();
+            result.push(Line::instruction(code, address, opcode));
+            address += length;
+        } else {
+            result.push(Line::Data {
+                address,
+                value: code[address],
+            });
+            address += 1;
+        }
+    }
+    result
+}
+
+pub fn listing(lines: &[Line]) -> String {
+    lines.iter().map(|l| format!("{}\n", l)).collect::<String>()
+}
+
+impl Programm {
+    pub fn disassemble(&self, follow_flow: bool) -> Vec<Line> {
+        let image = self.image();
+        if follow_flow {
+            reachable(&image)
+        } else {
+            linear(&image)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::asm::assemble;
+    use std::fs;
+
+    const INPUTS: [&str; 8] = [
+        "task_2", "task_5", "task_7", "task_9", "task_11", "task_13", "task_15", "task_17",
+    ];
+
+    #[test]
+    fn renders_instructions_and_modes() {
+        let lines = linear(&[1002, 4, 3, 4, 21101, -1, 7, 0, 109, 5, 99]);
+        let text = lines.iter().map(|l| l.to_string()).collect::<Vec<String>>();
+        assert_eq!(
+            text[0],
+            format!("{:>6}: {:<32} ; {}", 0, "MUL [4], #3 -> [4]", "1002,4,3,4")
+        );
+        assert!(text[1].contains("ADD #-1, #7 -> rb[0]"));
+        assert!(text[2].contains("ARB #5"));
+        assert!(text[3].contains("HLT"));
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[test]
+    fn unknown_cells_are_data() {
+        let lines = linear(&[104, 1, 42, 99]);
+        assert_eq!(lines[1].to_string(), format!("{:>6}: DATA 42", 2));
+        assert_eq!(lines[2].address(), 3);
+    }
+
+    #[test]
+    fn reachability_separates_data_from_code() {
+        let code = [1105, 1, 4, 104, 99];
+        let found = reachable_from(&code, 0);
+        assert_eq!(
+            found.keys().cloned().collect::<HashSet<usize>>(),
+            [0, 4].into()
+        );
+        let lines = reachable(&code);
+        assert!(matches!(lines[0], Line::Instruction { address: 0, .. }));
+        assert!(matches!(
+            lines[1],
+            Line::Data {
+                address: 3,
+                value: 104
+            }
+        ));
+        assert!(matches!(lines[2], Line::Instruction { address: 4, .. }));
+        assert!(matches!(
+            linear(&code)[1],
+            Line::Instruction { address: 3, .. }
+        ));
+    }
+
+    #[test]
+    fn reachability_follows_return_sites() {
+        // The call pushes its return address 7 and jumps through [5].
+        let code = [1101, 7, 0, 11, 1105, 1, 12, 4, 11, 99, 0, 0, 1106, 0, 11];
+        let found = reachable_from(&code, 0);
+        assert!(found.contains_key(&7));
+        assert!(!found.contains_key(&10));
+    }
+
+    #[test]
+    fn non_canonical_cells_round_trip() {
+        let code = [1104, 5, 10004, 6, 99];
+        let lines = linear(&code);
+        assert!(lines[0].to_string().contains("DATA 1104,5"));
+        assert!(lines[0].to_string().ends_with("; OUT #5"));
+        assert_eq!(assemble(&listing(&lines)), Ok(code.to_vec()));
+    }
+
+    #[test]
+    fn listings_of_every_input_reassemble() {
+        for name in INPUTS.iter() {
+            let image = fs::read_to_string(format!("input/{}", name))
+                .unwrap()
+                .parse::<Programm>()
+                .unwrap()
+                .image();
+            for follow_flow in &[false, true] {
+                let lines = if *follow_flow {
+                    reachable(&image)
+                } else {
+                    linear(&image)
+                };
+                assert_eq!(
+                    assemble(&listing(&lines)).as_ref(),
+                    Ok(&image),
+                    "{} with follow_flow {}",
+                    name,
+                    follow_flow
+                );
+            }
+        }
+    }
+}
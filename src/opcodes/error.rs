@@ -0,0 +1,72 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum VmError {
+    UnknownOpcode { address: usize, value: i64 },
+    UnknownMode { address: usize, value: i64 },
+    TruncatedInstruction { address: usize, length: usize },
+    WriteToImmediate { address: usize },
+    NegativeAddress { address: usize, value: i64 },
+    AddressOverflow { address: usize },
+    MemoryLimit { address: usize, target: usize, limit: usize },
+    ArithmeticOverflow { address: usize },
+    ValueOutOfRange { address: usize, target: usize },
+    TraceFailed { message: String },
+}
+
+impl Display for VmError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VmError::UnknownOpcode { address, value } => {
+                write!(f, "unknown opcode {} at address {}", value, address)
+            }
+            VmError::UnknownMode { address, value } => write!(
+                f,
+                "unknown parameter mode in {} at address {}",
+                value, address
+            ),
+            VmError::TruncatedInstruction { address, length } => write!(
+                f,
+                "instruction at address {} needs {} cells but the programm ends",
+                address, length
+            ),
+            VmError::WriteToImmediate { address } => write!(
+                f,
+                "instruction at address {} writes to an immediate operand",
+                address
+            ),
+            VmError::NegativeAddress { address, value } => write!(
+                f,
+                "instruction at address {} accesses negative address {}",
+                address, value
+            ),
+            VmError::AddressOverflow { address } => write!(
+                f,
+                "instruction at address {} computes an address out of range",
+                address
+            ),
+            VmError::MemoryLimit {
+                address,
+                target,
+                limit,
+            } => write!(
+                f,
+                "instruction at address {} writes to {} beyond the memory limit of {} cells",
+                address, target, limit
+            ),
+            VmError::ArithmeticOverflow { address } => write!(
+                f,
+                "instruction at address {} overflows 64-bit arithmetic",
+                address
+            ),
+            VmError::ValueOutOfRange { address, target } => write!(
+                f,
+                "instruction at address {} needs a 64-bit value but cell {} holds a big integer",
+                address, target
+            ),
+            VmError::TraceFailed { message } => write!(f, "trace failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for VmError {}
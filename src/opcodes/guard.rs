@@ -0,0 +1,156 @@
+use super::memory::Memory;
+use super::Programm;
+
+fn mix(address: usize, value: i64) -> u64 {
+    if value == 0 {
+        return 0;
+    }
+    let mut x = (address as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ value as u64;
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    x ^= x >> 33;
+    x = x.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    x ^ (x >> 33)
+}
+
+#[derive(Debug, Clone)]
+struct LoopDetector {
+    hash: u64,
+    saved: Option<(usize, i64, u64)>,
+    power: u64,
+    steps: u64,
+}
+
+impl LoopDetector {
+    fn new(memory: &Memory) -> Self {
+        let mut hash = 0;
+        for (start, cells) in memory.pages() {
+            for (i, value) in cells.iter().enumerate() {
+                hash ^= mix(start + i, *value);
+            }
+        }
+        LoopDetector {
+            hash,
+            saved: None,
+            power: 1,
+            steps: 0,
+        }
+    }
+
+    fn reset(&mut self) {
+        self.saved = None;
+        self.power = 1;
+        self.steps = 0;
+    }
+
+    fn repeats(&mut self, position: usize, relative_base: i64) -> bool {
+        let state = (position, relative_base, self.hash);
+        if self.saved == Some(state) {
+            return true;
+        }
+        self.steps += 1;
+        if self.saved.is_none() || self.steps == self.power {
+            self.saved = Some(state);
+            self.power *= 2;
+            self.steps = 0;
+        }
+        false
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Guard {
+    fuel: Option<u64>,
+    detector: Option<LoopDetector>,
+}
+
+impl Guard {
+    pub(super) fn exhausted(&self) -> bool {
+        self.fuel == Some(0)
+    }
+
+    pub(super) fn written(&mut self, address: usize, old: i64, new: i64) {
+        if let Some(detector) = self.detector.as_mut() {
+            detector.hash ^= mix(address, old) ^ mix(address, new);
+        }
+    }
+
+    pub(super) fn executed(&mut self, position: usize, relative_base: i64, io: bool) -> bool {
+        if let Some(fuel) = self.fuel.as_mut() {
+            *fuel = fuel.saturating_sub(1);
+        }
+        match self.detector.as_mut() {
+            Some(detector) if io => {
+                detector.reset();
+                false
+            }
+            Some(detector) => detector.repeats(position, relative_base),
+            None => false,
+        }
+    }
+}
+
+impl Programm {
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.guard.fuel = Some(fuel);
+        self
+    }
+
+    pub fn fuel(&self) -> Option<u64> {
+        self.guard.fuel
+    }
+
+    pub fn with_loop_detection(mut self) -> Self {
+        self.guard.detector = Some(LoopDetector::new(&self.code));
+        self
+    }
+
+    pub fn detects_loops(&self) -> bool {
+        self.guard.detector.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::StepOutcome;
+
+    fn programm(code: &str) -> Programm {
+        code.parse::<Programm>().unwrap()
+    }
+
+    #[test]
+    fn fuel_runs_out_in_a_spin() {
+        let mut programm = programm("1105,1,0").with_fuel(10);
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::OutOfFuel));
+        assert_eq!(programm.fuel(), Some(0));
+    }
+
+    #[test]
+    fn fuel_counts_instructions() {
+        let mut programm = programm("104,1,104,2,99").with_fuel(1);
+        assert_eq!(programm.step(), Ok(StepOutcome::Output(1)));
+        assert_eq!(programm.step(), Ok(StepOutcome::OutOfFuel));
+        assert_eq!(programm.position(), 2);
+    }
+
+    #[test]
+    fn detects_a_jump_to_itself() {
+        let mut programm = programm("1105,1,0").with_loop_detection();
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::LoopDetected));
+    }
+
+    #[test]
+    fn counting_loop_is_not_a_repeat() {
+        let mut programm = programm("1001,8,-1,8,1005,8,0,99,50").with_loop_detection();
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+    }
+
+    #[test]
+    fn output_resets_detection() {
+        let mut programm = programm("104,1,1105,1,0")
+            .with_fuel(100)
+            .with_loop_detection();
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::OutOfFuel));
+    }
+}
@@ -0,0 +1,60 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{Receiver, Sender};
+
+pub trait InputSource {
+    fn next_input(&mut self) -> Option<i64>;
+}
+
+pub trait OutputSink {
+    fn put(&mut self, value: i64);
+}
+
+impl InputSource for VecDeque<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.pop_front()
+    }
+}
+
+impl<F: FnMut() -> Option<i64>> InputSource for F {
+    fn next_input(&mut self) -> Option<i64> {
+        self()
+    }
+}
+
+pub struct IterInput<I>(pub I);
+
+impl<I: Iterator<Item = i64>> InputSource for IterInput<I> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.0.next()
+    }
+}
+
+impl InputSource for Receiver<i64> {
+    fn next_input(&mut self) -> Option<i64> {
+        self.recv().ok()
+    }
+}
+
+impl OutputSink for Vec<i64> {
+    fn put(&mut self, value: i64) {
+        self.push(value);
+    }
+}
+
+impl OutputSink for VecDeque<i64> {
+    fn put(&mut self, value: i64) {
+        self.push_back(value);
+    }
+}
+
+impl<F: FnMut(i64)> OutputSink for F {
+    fn put(&mut self, value: i64) {
+        self(value);
+    }
+}
+
+impl OutputSink for Sender<i64> {
+    fn put(&mut self, value: i64) {
+        let _ = self.send(value);
+    }
+}
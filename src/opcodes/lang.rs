@@ -0,0 +1,712 @@
+use super::asm;
+use super::Programm;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CompileError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl CompileError {
+    fn new(line: usize, message: String) -> Self {
+        CompileError { line, message }
+    }
+}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for CompileError {}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Symbol(&'static str),
+}
+
+const SYMBOLS: [&str; 19] = [
+    "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "<", ">", "!", "=", "(", ")", "{", "}", ",",
+    ";",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, CompileError> {
+    let mut result = vec![];
+    for (n, line) in source.lines().enumerate() {
+        let n = n + 1;
+        let line = line.split("//").next().unwrap();
+        let mut rest = line.trim_start();
+        while !rest.is_empty() {
+            let c = rest.chars().next().unwrap();
+            let length = if c.is_ascii_digit() {
+                let length = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let value = rest[..length].parse::<i64>().map_err(|_| {
+                    CompileError::new(n, format!("number `{}` is too large", &rest[..length]))
+                })?;
+                result.push((n, Token::Number(value)));
+                length
+            } else if c.is_ascii_alphabetic() || c == '_' {
+                let length = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                result.push((n, Token::Ident(rest[..length].to_string())));
+                length
+            } else {
+                let symbol =
+                    SYMBOLS
+                        .iter()
+                        .find(|s| rest.starts_with(*s))
+                        .ok_or(CompileError::new(
+                            n,
+                            format!("unexpected character `{}`", c),
+                        ))?;
+                result.push((n, Token::Symbol(symbol)));
+                symbol.len()
+            };
+            rest = rest[length..].trim_start();
+        }
+    }
+    Ok(result)
+}
+
+enum Expr {
+    Number(i64),
+    Var(String),
+    Call(String, Vec<Expr>),
+    Input,
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+enum Kind {
+    Let(String, Expr),
+    Assign(String, Expr),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Return(Expr),
+    Output(Expr),
+    Expr(Expr),
+}
+
+struct Stmt {
+    line: usize,
+    kind: Kind,
+}
+
+struct Function {
+    line: usize,
+    name: String,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+}
+
+const KEYWORDS: [&str; 8] = [
+    "fn", "let", "if", "else", "while", "return", "input", "output",
+];
+
+const LEVELS: [&[&str]; 5] = [
+    &["||"],
+    &["&&"],
+    &["==", "!=", "<", ">", "<=", ">="],
+    &["+", "-"],
+    &["*"],
+];
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    position: usize,
+}
+
+impl Parser {
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.position)
+            .or(self.tokens.last())
+            .map_or(1, |(n, _)| *n)
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, CompileError> {
+        Err(CompileError::new(self.line(), message))
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position).map(|(_, t)| t)
+    }
+
+    fn is(&self, symbol: &str) -> bool {
+        matches!(self.peek(), Some(Token::Symbol(s)) if *s == symbol)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Some(Token::Ident(s)) if s == keyword)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).map(|(_, t)| t.clone());
+        self.position += 1;
+        token
+    }
+
+    fn expect(&mut self, symbol: &str) -> Result<(), CompileError> {
+        if self.is(symbol) {
+            self.position += 1;
+            Ok(())
+        } else {
+            self.error(format!("expected `{}`", symbol))
+        }
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        let found = self.is_keyword(keyword);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn name(&mut self) -> Result<String, CompileError> {
+        match self.peek() {
+            Some(Token::Ident(s)) if !KEYWORDS.contains(&s.as_str()) => {
+                let s = s.clone();
+                self.position += 1;
+                Ok(s)
+            }
+            _ => self.error("expected a name".to_string()),
+        }
+    }
+
+    fn list<T, F>(&mut self, mut item: F) -> Result<Vec<T>, CompileError>
+    where
+        F: FnMut(&mut Self) -> Result<T, CompileError>,
+    {
+        self.expect("(")?;
+        let mut result = vec![];
+        while !self.is(")") {
+            result.push(item(self)?);
+            if !self.is(")") {
+                self.expect(",")?;
+            }
+        }
+        self.expect(")")?;
+        Ok(result)
+    }
+
+    fn function(&mut self) -> Result<Function, CompileError> {
+        let line = self.line();
+        if !self.keyword("fn") {
+            return self.error("expected `fn`".to_string());
+        }
+        let name = self.name()?;
+        let params = self.list(Self::name)?;
+        let body = self.block()?;
+        Ok(Function {
+            line,
+            name,
+            params,
+            body,
+        })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>, CompileError> {
+        self.expect("{")?;
+        let mut result = vec![];
+        while !self.is("}") {
+            if self.peek().is_none() {
+                return self.error("unclosed block".to_string());
+            }
+            result.push(self.statement()?);
+        }
+        self.expect("}")?;
+        Ok(result)
+    }
+
+    fn statement(&mut self) -> Result<Stmt, CompileError> {
+        let line = self.line();
+        let kind = if self.keyword("let") {
+            let name = self.name()?;
+            self.expect("=")?;
+            Kind::Let(name, self.expression(0)?)
+        } else if self.keyword("if") {
+            let condition = self.expression(0)?;
+            let then = self.block()?;
+            let otherwise = if !self.keyword("else") {
+                vec![]
+            } else if self.is_keyword("if") {
+                vec![self.statement()?]
+            } else {
+                self.block()?
+            };
+            return Ok(Stmt {
+                line,
+                kind: Kind::If(condition, then, otherwise),
+            });
+        } else if self.keyword("while") {
+            let condition = self.expression(0)?;
+            let body = self.block()?;
+            return Ok(Stmt {
+                line,
+                kind: Kind::While(condition, body),
+            });
+        } else if self.keyword("return") {
+            Kind::Return(if self.is(";") {
+                Expr::Number(0)
+            } else {
+                self.expression(0)?
+            })
+        } else if self.keyword("output") {
+            self.expect("(")?;
+            let value = self.expression(0)?;
+            self.expect(")")?;
+            Kind::Output(value)
+        } else if matches!(
+            self.tokens.get(self.position + 1),
+            Some((_, Token::Symbol("=")))
+        ) {
+            let name = self.name()?;
+            self.expect("=")?;
+            Kind::Assign(name, self.expression(0)?)
+        } else {
+            Kind::Expr(self.expression(0)?)
+        };
+        self.expect(";")?;
+        Ok(Stmt { line, kind })
+    }
+
+    fn expression(&mut self, level: usize) -> Result<Expr, CompileError> {
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+        let mut left = self.expression(level + 1)?;
+        while let Some(Token::Symbol(op)) = self.peek() {
+            let op = *op;
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+            self.position += 1;
+            let right = self.expression(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Expr, CompileError> {
+        for op in ["-", "!"] {
+            if self.is(op) {
+                self.position += 1;
+                return Ok(Expr::Unary(op, Box::new(self.unary()?)));
+            }
+        }
+        match self.next() {
+            Some(Token::Number(v)) => Ok(Expr::Number(v)),
+            Some(Token::Symbol("(")) => {
+                let inner = self.expression(0)?;
+                self.expect(")")?;
+                Ok(inner)
+            }
+            Some(Token::Ident(name)) if name == "input" => {
+                self.list(|p| p.error::<()>("`input` takes no arguments".to_string()))?;
+                Ok(Expr::Input)
+            }
+            Some(Token::Ident(name)) if !KEYWORDS.contains(&name.as_str()) => {
+                if self.is("(") {
+                    Ok(Expr::Call(name, self.list(|p| p.expression(0))?))
+                } else {
+                    Ok(Expr::Var(name))
+                }
+            }
+            _ => {
+                self.position -= 1;
+                self.error("expected an expression".to_string())
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Operand {
+    Immediate(i64),
+    Slot(i64),
+}
+
+impl Display for Operand {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Operand::Immediate(v) => write!(f, "#{}", v),
+            Operand::Slot(s) => write!(f, "rb[{}]", s),
+        }
+    }
+}
+
+struct Frame {
+    scopes: Vec<HashMap<String, i64>>,
+    locals: i64,
+    next: i64,
+}
+
+struct Codegen<'a> {
+    arity: HashMap<&'a str, usize>,
+    lines: Vec<String>,
+    labels: usize,
+    frame: Frame,
+    line: usize,
+}
+
+impl<'a> Codegen<'a> {
+    fn emit(&mut self, instruction: String) {
+        self.lines.push(format!("    {}", instruction));
+    }
+
+    fn label(&mut self) -> String {
+        self.labels += 1;
+        format!("_l{}", self.labels)
+    }
+
+    fn place(&mut self, label: &str) {
+        self.lines.push(format!("{}:", label));
+    }
+
+    fn temp(&mut self) -> i64 {
+        self.frame.next += 1;
+        self.frame.next - 1
+    }
+
+    fn error<T>(&self, message: String) -> Result<T, CompileError> {
+        Err(CompileError::new(self.line, message))
+    }
+
+    fn lookup(&self, name: &str) -> Result<i64, CompileError> {
+        match self.frame.scopes.iter().rev().find_map(|s| s.get(name)) {
+            Some(slot) => Ok(*slot),
+            None => self.error(format!("unknown variable `{}`", name)),
+        }
+    }
+
+    fn op(&mut self, mnemonic: &str, left: Operand, right: Operand) -> Operand {
+        let to = self.temp();
+        self.emit(format!("{} {}, {} -> rb[{}]", mnemonic, left, right, to));
+        Operand::Slot(to)
+    }
+
+    fn copy(&mut self, value: Operand, slot: i64) {
+        if value != Operand::Slot(slot) {
+            self.emit(format!("add {}, #0 -> rb[{}]", value, slot));
+        }
+    }
+
+    fn not(&mut self, value: Operand) -> Operand {
+        self.op("eq", value, Operand::Immediate(0))
+    }
+
+    fn truth(&mut self, value: Operand, slot: i64) {
+        match value {
+            Operand::Immediate(v) => self.copy(Operand::Immediate((v != 0) as i64), slot),
+            _ => {
+                self.emit(format!("eq {}, #0 -> rb[{}]", value, slot));
+                self.emit(format!("eq rb[{}], #0 -> rb[{}]", slot, slot));
+            }
+        }
+    }
+
+    fn short_circuit(
+        &mut self,
+        op: &str,
+        left: &Expr,
+        right: &Expr,
+    ) -> Result<Operand, CompileError> {
+        let stop = (op == "||") as i64;
+        let left = self.expression(left)?;
+        if let Operand::Immediate(v) = left {
+            if (v != 0) as i64 == stop {
+                return Ok(Operand::Immediate(stop));
+            }
+            return Ok(match self.expression(right)? {
+                Operand::Immediate(v) => Operand::Immediate((v != 0) as i64),
+                value => {
+                    let to = self.temp();
+                    self.truth(value, to);
+                    Operand::Slot(to)
+                }
+            });
+        }
+        let (to, end) = (self.temp(), self.label());
+        self.copy(Operand::Immediate(stop), to);
+        let jump = if stop == 0 { "jf" } else { "jt" };
+        self.emit(format!("{} {}, #{}", jump, left, end));
+        let value = self.expression(right)?;
+        self.truth(value, to);
+        self.place(&end);
+        Ok(Operand::Slot(to))
+    }
+
+    fn fold(op: &str, l: i64, r: i64) -> Option<i64> {
+        Some(match op {
+            "+" => l.checked_add(r)?,
+            "-" => l.checked_sub(r)?,
+            "*" => l.checked_mul(r)?,
+            "==" => (l == r) as i64,
+            "!=" => (l != r) as i64,
+            "<" => (l < r) as i64,
+            ">" => (l > r) as i64,
+            "<=" => (l <= r) as i64,
+            ">=" => (l >= r) as i64,
+            _ => return None,
+        })
+    }
+
+    fn expression(&mut self, expr: &Expr) -> Result<Operand, CompileError> {
+        Ok(match expr {
+            Expr::Number(v) => Operand::Immediate(*v),
+            Expr::Var(name) => Operand::Slot(self.lookup(name)?),
+            Expr::Input => {
+                let to = self.temp();
+                self.emit(format!("in rb[{}]", to));
+                Operand::Slot(to)
+            }
+            Expr::Unary(op, inner) => {
+                let value = self.expression(inner)?;
+                match (*op, value) {
+                    ("-", Operand::Immediate(v)) if v != i64::MIN => Operand::Immediate(-v),
+                    ("-", _) => self.op("mul", value, Operand::Immediate(-1)),
+                    (_, Operand::Immediate(v)) => Operand::Immediate((v == 0) as i64),
+                    _ => self.not(value),
+                }
+            }
+            Expr::Binary(op, left, right) if *op == "&&" || *op == "||" => {
+                self.short_circuit(op, left, right)?
+            }
+            Expr::Binary(op, left, right) => {
+                let l = self.expression(left)?;
+                let r = self.expression(right)?;
+                if let (Operand::Immediate(a), Operand::Immediate(b)) = (l, r) {
+                    if let Some(v) = Self::fold(op, a, b) {
+                        return Ok(Operand::Immediate(v));
+                    }
+                }
+                match *op {
+                    "+" => self.op("add", l, r),
+                    "-" => match r {
+                        Operand::Immediate(v) if v != i64::MIN => {
+                            self.op("add", l, Operand::Immediate(-v))
+                        }
+                        _ => {
+                            let negated = self.op("mul", r, Operand::Immediate(-1));
+                            self.op("add", l, negated)
+                        }
+                    },
+                    "*" => self.op("mul", l, r),
+                    "==" => self.op("eq", l, r),
+                    "<" => self.op("lt", l, r),
+                    ">" => self.op("lt", r, l),
+                    "!=" => {
+                        let equal = self.op("eq", l, r);
+                        self.not(equal)
+                    }
+                    "<=" => {
+                        let greater = self.op("lt", r, l);
+                        self.not(greater)
+                    }
+                    _ => {
+                        let less = self.op("lt", l, r);
+                        self.not(less)
+                    }
+                }
+            }
+            Expr::Call(name, args) => {
+                match self.arity.get(name.as_str()) {
+                    None => return self.error(format!("unknown function `{}`", name)),
+                    Some(n) if *n != args.len() => {
+                        return self.error(format!(
+                            "`{}` takes {} arguments, got {}",
+                            name,
+                            n,
+                            args.len()
+                        ))
+                    }
+                    _ => (),
+                }
+                let base = self.temp();
+                for (i, arg) in args.iter().enumerate() {
+                    let slot = base + 1 + i as i64;
+                    self.frame.next = slot;
+                    let value = self.expression(arg)?;
+                    self.copy(value, slot);
+                }
+                self.frame.next = base + 2 + args.len().saturating_sub(1) as i64;
+                let back = self.label();
+                self.emit(format!("arb #{}", base));
+                self.emit(format!("add #{}, #0 -> rb[0]", back));
+                self.emit(format!("jt #1, #fn_{}", name));
+                self.place(&back);
+                self.emit(format!("arb #{}", -base));
+                Operand::Slot(base + 1)
+            }
+        })
+    }
+
+    fn jump_unless(&mut self, condition: &Expr, label: &str) -> Result<(), CompileError> {
+        match self.expression(condition)? {
+            Operand::Immediate(0) => self.emit(format!("jt #1, #{}", label)),
+            Operand::Immediate(_) => (),
+            value => self.emit(format!("jf {}, #{}", value, label)),
+        }
+        Ok(())
+    }
+
+    fn block(&mut self, body: &[Stmt]) -> Result<(), CompileError> {
+        self.frame.scopes.push(HashMap::new());
+        for stmt in body {
+            self.statement(stmt)?;
+        }
+        self.frame.scopes.pop();
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<(), CompileError> {
+        self.line = stmt.line;
+        self.frame.next = self.frame.locals;
+        match &stmt.kind {
+            Kind::Let(name, value) => {
+                let value = self.expression(value)?;
+                let slot = self.frame.locals;
+                self.frame.locals += 1;
+                self.copy(value, slot);
+                self.frame
+                    .scopes
+                    .last_mut()
+                    .unwrap()
+                    .insert(name.clone(), slot);
+            }
+            Kind::Assign(name, value) => {
+                let slot = self.lookup(name)?;
+                let value = self.expression(value)?;
+                self.copy(value, slot);
+            }
+            Kind::If(condition, then, otherwise) => {
+                let (skip, end) = (self.label(), self.label());
+                self.jump_unless(condition, &skip)?;
+                self.block(then)?;
+                if !otherwise.is_empty() {
+                    self.emit(format!("jt #1, #{}", end));
+                }
+                self.place(&skip);
+                self.block(otherwise)?;
+                self.place(&end);
+            }
+            Kind::While(condition, body) => {
+                let (top, end) = (self.label(), self.label());
+                self.place(&top);
+                self.jump_unless(condition, &end)?;
+                self.block(body)?;
+                self.emit(format!("jt #1, #{}", top));
+                self.place(&end);
+            }
+            Kind::Return(value) => {
+                let value = self.expression(value)?;
+                self.copy(value, 1);
+                self.emit("jf #0, rb[0]".to_string());
+            }
+            Kind::Output(value) => {
+                let value = self.expression(value)?;
+                self.emit(format!("out {}", value));
+            }
+            Kind::Expr(value) => {
+                self.expression(value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn function(&mut self, function: &Function) -> Result<(), CompileError> {
+        self.line = function.line;
+        let mut params = HashMap::new();
+        for (i, name) in function.params.iter().enumerate() {
+            if params.insert(name.clone(), i as i64 + 1).is_some() {
+                return self.error(format!("duplicate parameter `{}`", name));
+            }
+        }
+        let locals = function.params.len() as i64 + 1;
+        self.frame = Frame {
+            scopes: vec![params],
+            locals,
+            next: locals,
+        };
+        self.place(&format!("fn_{}", function.name));
+        self.block(&function.body)?;
+        self.emit("add #0, #0 -> rb[1]".to_string());
+        self.emit("jf #0, rb[0]".to_string());
+        Ok(())
+    }
+}
+
+pub fn compile(source: &str) -> Result<String, CompileError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        position: 0,
+    };
+    let mut functions = vec![];
+    while parser.peek().is_some() {
+        functions.push(parser.function()?);
+    }
+    let mut arity = HashMap::new();
+    for function in functions.iter() {
+        if arity
+            .insert(function.name.as_str(), function.params.len())
+            .is_some()
+        {
+            return Err(CompileError::new(
+                function.line,
+                format!("duplicate function `{}`", function.name),
+            ));
+        }
+    }
+    match arity.get("main") {
+        Some(0) => (),
+        Some(_) => {
+            return Err(CompileError::new(
+                1,
+                "`main` takes no arguments".to_string(),
+            ))
+        }
+        None => return Err(CompileError::new(1, "no `main` function".to_string())),
+    }
+    let mut codegen = Codegen {
+        arity,
+        lines: vec![],
+        labels: 0,
+        frame: Frame {
+            scopes: vec![],
+            locals: 0,
+            next: 0,
+        },
+        line: 1,
+    };
+    codegen.emit("arb #_stack".to_string());
+    codegen.emit("add #_halt, #0 -> rb[0]".to_string());
+    codegen.emit("jt #1, #fn_main".to_string());
+    codegen.place("_halt");
+    codegen.emit("hlt".to_string());
+    for function in functions.iter() {
+        codegen.function(function)?;
+    }
+    codegen.place("_stack");
+    codegen.emit("data 0".to_string());
+    let mut result = codegen.lines.join("\n");
+    result.push('\n');
+    Ok(result)
+}
+
+impl Programm {
+    pub fn compile(source: &str) -> Result<Programm, CompileError> {
+        let assembly = compile(source)?;
+        Ok(Programm::from(
+            asm::assemble(&assembly).expect("generated assembly is valid"),
+        ))
+    }
+}
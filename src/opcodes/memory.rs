@@ -0,0 +1,206 @@
+use std::collections::HashMap;
+use std::ops::RangeInclusive;
+
+const PAGE_BITS: usize = 10;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+const DENSE_PAGES: usize = 4096;
+pub const DEFAULT_LIMIT: usize = 1 << 26;
+
+type Page = Box<[i64; PAGE_SIZE]>;
+
+#[derive(Clone)]
+pub struct Memory {
+    dense: Vec<Option<Page>>,
+    sparse: HashMap<usize, Page>,
+    allocated: usize,
+    limit: usize,
+    extent: usize,
+}
+
+impl Memory {
+    pub fn with_limit(limit: usize) -> Self {
+        Memory {
+            dense: vec![],
+            sparse: HashMap::new(),
+            allocated: 0,
+            limit,
+            extent: 0,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn set_limit(&mut self, limit: usize) {
+        self.limit = limit;
+    }
+
+    pub fn extent(&self) -> usize {
+        self.extent
+    }
+
+    fn page(&self, index: usize) -> Option<&Page> {
+        if index < DENSE_PAGES {
+            self.dense.get(index).and_then(|p| p.as_ref())
+        } else {
+            self.sparse.get(&index)
+        }
+    }
+
+    fn page_mut(&mut self, index: usize) -> Option<&mut Page> {
+        if index < DENSE_PAGES {
+            self.dense.get_mut(index).and_then(|p| p.as_mut())
+        } else {
+            self.sparse.get_mut(&index)
+        }
+    }
+
+    pub fn get(&self, address: usize) -> i64 {
+        match self.page(address >> PAGE_BITS) {
+            Some(page) => page[address & (PAGE_SIZE - 1)],
+            None => 0,
+        }
+    }
+
+    pub fn set(&mut self, address: usize, value: i64) -> bool {
+        let index = address >> PAGE_BITS;
+        if self.page(index).is_none() {
+            if value == 0 {
+                self.touch(address);
+                return true;
+            }
+            // The limit is counted in cells but granted in whole pages.
+            if self.allocated >= self.limit.div_ceil(PAGE_SIZE) {
+                return false;
+            }
+            let page = Box::new([0; PAGE_SIZE]);
+            if index < DENSE_PAGES {
+                if self.dense.len() <= index {
+                    self.dense.resize_with(index + 1, || None);
+                }
+                self.dense[index] = Some(page);
+            } else {
+                self.sparse.insert(index, page);
+            }
+            self.allocated += 1;
+        }
+        self.page_mut(index).unwrap()[address & (PAGE_SIZE - 1)] = value;
+        self.touch(address);
+        true
+    }
+
+    fn touch(&mut self, address: usize) {
+        if address >= self.extent {
+            self.extent = address.saturating_add(1);
+        }
+    }
+
+    // Every page of the range is allocated or follows an allocated one, whose
+    // last instruction may read operands from a page of zeros.
+    pub fn covers(&self, range: &RangeInclusive<usize>) -> bool {
+        let mut index = range.start() >> PAGE_BITS;
+        loop {
+            if self.page(index).is_none() && (index == 0 || self.page(index - 1).is_none()) {
+                return false;
+            }
+            if index >= range.end() >> PAGE_BITS {
+                return true;
+            }
+            index += 1;
+        }
+    }
+
+    pub fn set_extent(&mut self, extent: usize) {
+        self.extent = extent;
+    }
+
+    pub fn pages(&self) -> Vec<(usize, &[i64])> {
+        let mut result = self
+            .dense
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| p.as_ref().map(|p| (i, p)))
+            .chain(self.sparse.iter().map(|(i, p)| (*i, p)))
+            .map(|(i, p)| (i << PAGE_BITS, &p[..]))
+            .collect::<Vec<(usize, &[i64])>>();
+        result.sort_by_key(|(address, _)| *address);
+        result
+    }
+
+    // Sparse pages stay out of the flat image, use `pages` to see them.
+    pub fn image(&self) -> Vec<i64> {
+        let end = self.extent.min(DENSE_PAGES * PAGE_SIZE);
+        (0..end).map(|a| self.get(a)).collect()
+    }
+}
+
+impl From<Vec<i64>> for Memory {
+    fn from(code: Vec<i64>) -> Self {
+        let mut memory = Memory::with_limit(std::cmp::max(
+            DEFAULT_LIMIT,
+            code.len().div_ceil(PAGE_SIZE) * PAGE_SIZE,
+        ));
+        for (address, value) in code.iter().enumerate() {
+            memory.set(address, *value);
+        }
+        memory.extent = code.len();
+        memory
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allocates_pages_only_for_nonzero_writes() {
+        let mut memory = Memory::with_limit(DEFAULT_LIMIT);
+        assert!(memory.set(5000, 0));
+        assert!(memory.pages().is_empty());
+        assert!(memory.set(5000, 7));
+        assert!(memory.set(5001, 8));
+        assert_eq!(memory.pages().len(), 1);
+        assert_eq!(memory.pages()[0].0, 4 * PAGE_SIZE);
+        assert_eq!(
+            (memory.get(5000), memory.get(5001), memory.get(4999)),
+            (7, 8, 0)
+        );
+    }
+
+    #[test]
+    fn extent_follows_the_highest_touched_cell() {
+        let mut memory = Memory::from(vec![1, 2, 3]);
+        assert_eq!(memory.extent(), 3);
+        memory.set(10, 0);
+        assert_eq!(memory.extent(), 11);
+        memory.set(4, 1);
+        assert_eq!(memory.extent(), 11);
+        assert_eq!(memory.image(), vec![1, 2, 3, 0, 1, 0, 0, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn limit_is_granted_in_whole_pages() {
+        let mut memory = Memory::with_limit(1500);
+        assert!(memory.set(0, 1));
+        assert!(memory.set(PAGE_SIZE, 1));
+        assert!(!memory.set(2 * PAGE_SIZE, 1));
+        assert!(memory.set(2 * PAGE_SIZE, 0));
+        assert_eq!(memory.get(2 * PAGE_SIZE), 0);
+        assert_eq!(memory.pages().len(), 2);
+    }
+
+    #[test]
+    fn high_addresses_stay_sparse() {
+        let mut memory = Memory::with_limit(2 * PAGE_SIZE);
+        assert!(memory.set(usize::MAX, 9));
+        assert!(memory.set(1 << 40, 3));
+        assert_eq!((memory.get(usize::MAX), memory.get(1 << 40)), (9, 3));
+        assert_eq!(memory.extent(), usize::MAX);
+        assert!(memory.dense.is_empty());
+        assert_eq!(memory.image().len(), DENSE_PAGES * PAGE_SIZE);
+        let pages = memory.pages();
+        assert_eq!(pages.len(), 2);
+        assert!(pages[0].0 < pages[1].0);
+    }
+}
@@ -1,6 +1,45 @@
+use num::BigInt;
+use std::collections::VecDeque;
+use std::path::Path;
 use std::str::FromStr;
 
-#[derive(Debug)]
+pub mod aot;
+mod arith;
+pub mod ascii;
+pub mod asm;
+pub mod cfg;
+pub mod conformance;
+pub mod decompile;
+pub mod disasm;
+mod error;
+mod guard;
+mod io;
+pub mod lang;
+mod memory;
+pub mod network;
+pub mod parallel;
+mod parse;
+pub mod profile;
+pub mod snapshot;
+pub mod sweep;
+pub mod symbolic;
+mod trace;
+pub mod watch;
+
+pub use arith::Arithmetic;
+pub use error::VmError;
+pub use io::{InputSource, IterInput, OutputSink};
+pub use parse::{ParseError, ParseMode};
+
+const CACHED_CELLS: usize = 1 << 16;
+use arith::BigCells;
+use guard::Guard;
+use memory::Memory;
+use profile::Profile;
+use trace::{Trace, Tracer};
+use watch::{Access, Watches};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Mode {
     Position(usize),
     Immediate(i64),
@@ -8,50 +47,60 @@ pub enum Mode {
 }
 
 impl Mode {
-    fn get(&self, v: &Programm) -> i64 {
+    fn address(&self, v: &Programm) -> Result<usize, VmError> {
         match self {
-            Mode::Immediate(x) => x.clone(),
-            Mode::Position(p) => {
-                if v.code.len() <= *p {
-                    0
-                } else {
-                    v.code[*p].clone()
-                }
-            }
+            Mode::Position(p) => Ok(*p),
             Mode::Relative(p) => {
-                let ind = (v.relative_base as i64 + *p) as usize;
-                if v.code.len() <= ind {
-                    0
+                let ind = v
+                    .relative_base
+                    .checked_add(*p)
+                    .ok_or(VmError::AddressOverflow {
+                        address: v.position,
+                    })?;
+                if ind < 0 {
+                    Err(VmError::NegativeAddress {
+                        address: v.position,
+                        value: ind,
+                    })
                 } else {
-                    v.code[ind].clone()
+                    Ok(ind as usize)
                 }
             }
+            Mode::Immediate(_) => Err(VmError::WriteToImmediate {
+                address: v.position,
+            }),
         }
     }
 
-    fn set(&self, v: &mut Programm, value: i64) {
-        match self {
-            Mode::Position(p) => {
-                if v.code.len() <= *p {
-                    v.code
-                        .extend(std::iter::repeat(0).take(*p - v.code.len() + 1));
-                }
-                v.code[*p] = value;
-            }
-            Mode::Relative(p) => {
-                let ind = (v.relative_base as i64 + *p) as usize;
-                if v.code.len() <= ind {
-                    v.code
-                        .extend(std::iter::repeat(0).take(ind - v.code.len() + 1));
+    fn get(&self, v: &mut Programm) -> Result<i64, VmError> {
+        let value = match self {
+            Mode::Immediate(x) => *x,
+            _ => {
+                let address = self.address(v)?;
+                if v.big.contains(address) {
+                    return Err(VmError::ValueOutOfRange {
+                        address: v.position,
+                        target: address,
+                    });
                 }
-                v.code[ind] = value;
+                let value = v.read(address);
+                v.watched(Access::Read, address, value);
+                value
             }
-            _ => (),
-        }
+        };
+        v.traced(|t| t.operand(value));
+        Ok(value)
+    }
+
+    fn set(&self, v: &mut Programm, value: i64) -> Result<(), VmError> {
+        let ind = self.address(v)?;
+        v.traced(|t| t.write(ind, value));
+        v.watched(Access::Write, ind, value);
+        v.write(ind, value)
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 pub enum Opcode {
     Add { left: Mode, right: Mode, to: Mode },
     Multiply { left: Mode, right: Mode, to: Mode },
@@ -66,184 +115,471 @@ pub enum Opcode {
 }
 
 impl Opcode {
-    fn advance_by(&self) -> usize {
+    pub fn length(&self) -> usize {
         match self {
             Opcode::Add { .. } => 4,
             Opcode::Multiply { .. } => 4,
-            Opcode::Halt => 0,
+            Opcode::Halt => 1,
             Opcode::Input { .. } => 2,
             Opcode::Output { .. } => 2,
-            Opcode::JumpIfFalse { .. } => 0,
-            Opcode::JumpIfTrue { .. } => 0,
+            Opcode::JumpIfFalse { .. } => 3,
+            Opcode::JumpIfTrue { .. } => 3,
             Opcode::Less { .. } => 4,
             Opcode::Equal { .. } => 4,
             Opcode::AdjustRelativeBase { .. } => 2,
         }
     }
 
-    fn parse_mode(mode: char, val: i64) -> Mode {
-        if mode == '0' {
-            Mode::Position(val as usize)
-        } else if mode == '1' {
-            Mode::Immediate(val)
-        } else {
-            Mode::Relative(val)
+    fn parse_mode(mode: i64, val: i64) -> Option<Mode> {
+        match mode {
+            0 => Some(Mode::Position(val as usize)),
+            1 => Some(Mode::Immediate(val)),
+            2 => Some(Mode::Relative(val)),
+            _ => None,
+        }
+    }
+
+    fn command_length(command: i64) -> usize {
+        match command {
+            1 | 2 | 7 | 8 => 4,
+            5 | 6 => 3,
+            3 | 4 | 9 => 2,
+            _ => 1,
         }
     }
 
-    fn from_vec(v: &Vec<i64>, position: usize) -> Self {
-        let command = format!("{:05}", v[position]).chars().collect::<Vec<char>>();
-        let mode_3 = command[0];
-        let mode_2 = command[1];
-        let mode_1 = command[2];
-        match (command[3], command[4]) {
-            ('0', '1') => Opcode::Add {
-                left: Self::parse_mode(mode_1, v[position + 1]),
-                right: Self::parse_mode(mode_2, v[position + 2]),
-                to: Self::parse_mode(mode_3, v[position + 3]),
+    fn from_vec(v: &[i64], position: usize) -> Result<Self, VmError> {
+        Self::from_cells(|a| v.get(a).cloned().unwrap_or(0), v.len(), position)
+    }
+
+    fn from_cells<F>(cell: F, extent: usize, position: usize) -> Result<Self, VmError>
+    where
+        F: Fn(usize) -> i64,
+    {
+        let value = cell(position);
+        let unknown = VmError::UnknownOpcode {
+            address: position,
+            value,
+        };
+        if !(0..100_000).contains(&value) {
+            return Err(unknown);
+        }
+        let command = value % 100;
+        let mode_1 = value / 100 % 10;
+        let mode_2 = value / 1_000 % 10;
+        let mode_3 = value / 10_000;
+        let length = Self::command_length(command);
+        if extent < position + length {
+            return Err(VmError::TruncatedInstruction {
+                address: position,
+                length,
+            });
+        }
+        let arg = |n: usize, mode: i64| -> Result<Mode, VmError> {
+            let val = cell(position + n);
+            if mode == 0 && val < 0 {
+                return Err(VmError::NegativeAddress {
+                    address: position,
+                    value: val,
+                });
+            }
+            Self::parse_mode(mode, val).ok_or(VmError::UnknownMode {
+                address: position,
+                value,
+            })
+        };
+        Ok(match command {
+            1 => Opcode::Add {
+                left: arg(1, mode_1)?,
+                right: arg(2, mode_2)?,
+                to: arg(3, mode_3)?,
             },
-            ('0', '2') => Opcode::Multiply {
-                left: Self::parse_mode(mode_1, v[position + 1]),
-                right: Self::parse_mode(mode_2, v[position + 2]),
-                to: Self::parse_mode(mode_3, v[position + 3]),
+            2 => Opcode::Multiply {
+                left: arg(1, mode_1)?,
+                right: arg(2, mode_2)?,
+                to: arg(3, mode_3)?,
             },
-            ('0', '3') => Opcode::Input {
-                to: Self::parse_mode(mode_1, v[position + 1]),
+            3 => Opcode::Input {
+                to: arg(1, mode_1)?,
             },
-            ('0', '4') => Opcode::Output {
-                from: Self::parse_mode(mode_1, v[position + 1]),
+            4 => Opcode::Output {
+                from: arg(1, mode_1)?,
             },
-            ('0', '5') => Opcode::JumpIfTrue {
-                check: Self::parse_mode(mode_1, v[position + 1]),
-                to: Self::parse_mode(mode_2, v[position + 2]),
+            5 => Opcode::JumpIfTrue {
+                check: arg(1, mode_1)?,
+                to: arg(2, mode_2)?,
             },
-            ('0', '6') => Opcode::JumpIfFalse {
-                check: Self::parse_mode(mode_1, v[position + 1]),
-                to: Self::parse_mode(mode_2, v[position + 2]),
+            6 => Opcode::JumpIfFalse {
+                check: arg(1, mode_1)?,
+                to: arg(2, mode_2)?,
             },
-            ('0', '7') => Opcode::Less {
-                left: Self::parse_mode(mode_1, v[position + 1]),
-                right: Self::parse_mode(mode_2, v[position + 2]),
-                to: Self::parse_mode(mode_3, v[position + 3]),
+            7 => Opcode::Less {
+                left: arg(1, mode_1)?,
+                right: arg(2, mode_2)?,
+                to: arg(3, mode_3)?,
             },
-            ('0', '8') => Opcode::Equal {
-                left: Self::parse_mode(mode_1, v[position + 1]),
-                right: Self::parse_mode(mode_2, v[position + 2]),
-                to: Self::parse_mode(mode_3, v[position + 3]),
+            8 => Opcode::Equal {
+                left: arg(1, mode_1)?,
+                right: arg(2, mode_2)?,
+                to: arg(3, mode_3)?,
             },
-            ('0', '9') => Opcode::AdjustRelativeBase {
-                to: Self::parse_mode(mode_1, v[position + 1]),
+            9 => Opcode::AdjustRelativeBase {
+                to: arg(1, mode_1)?,
             },
-            ('9', '9') => Opcode::Halt,
-            _ => panic!("Unknown command"),
-        }
+            99 => Opcode::Halt,
+            _ => return Err(unknown),
+        })
     }
 }
 
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum StepOutcome {
+    Continued,
+    NeedInput,
+    Output(i64),
+    Halted,
+    OutOfFuel,
+    LoopDetected,
+    Watchpoint,
+}
+
 #[derive(Clone)]
 pub struct Programm {
-    code: Vec<i64>,
+    code: Memory,
+    decoded: Vec<Option<Opcode>>,
     position: usize,
     is_finished: bool,
-    relative_base: usize,
+    relative_base: i64,
+    inputs: VecDeque<i64>,
+    trace: Trace,
+    profile: Option<Box<Profile>>,
+    arithmetic: Arithmetic,
+    big: BigCells,
+    guard: Guard,
+    watches: Option<Box<Watches>>,
 }
 
-impl FromStr for Programm {
-    type Err = ();
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(Programm {
-            code: s.split(",").filter_map(|l| l.parse::<i64>().ok()).collect(),
+impl From<Vec<i64>> for Programm {
+    fn from(code: Vec<i64>) -> Self {
+        Programm {
+            code: Memory::from(code),
+            decoded: vec![],
             position: 0,
             is_finished: false,
             relative_base: 0,
-        })
+            inputs: VecDeque::new(),
+            trace: Trace::default(),
+            profile: None,
+            arithmetic: Arithmetic::default(),
+            big: BigCells::default(),
+            guard: Guard::default(),
+            watches: None,
+        }
+    }
+}
+
+impl FromStr for Programm {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Programm::parse(s, ParseMode::Strict)
     }
 }
 
 impl Programm {
+    pub fn parse(source: &str, mode: ParseMode) -> Result<Programm, ParseError> {
+        parse::parse(source, mode).map(Programm::from)
+    }
+
     pub fn is_finished(&self) -> bool {
         self.is_finished
     }
-    pub fn alter(&mut self, position: usize, value: i64) {
-        self.code[position] = value;
+
+    pub fn alter(&mut self, position: usize, value: i64) -> Result<(), VmError> {
+        self.write(position, value)
     }
 
-    pub fn run(&mut self, inputs: &mut Vec<i64>) -> Vec<i64> {
-        let mut result = vec![];
+    pub fn with_memory_limit(mut self, cells: usize) -> Self {
+        self.code.set_limit(cells);
+        self
+    }
 
-        loop {
-            let before_modifications = self.code[self.position].clone();
-            let command = Opcode::from_vec(&self.code, self.position);
-
-            match &command {
-                Opcode::Add { left, right, to } => {
-                    let l = left.get(self);
-                    let r = right.get(self);
-                    to.set(self, l + r);
-                }
-                Opcode::Multiply { left, right, to } => {
-                    let l = left.get(self);
-                    let r = right.get(self);
-                    to.set(self, l * r);
-                }
-                Opcode::Input { to } => {
-                    if inputs.is_empty() {
-                        break;
-                    } else {
-                        to.set(self, inputs.pop().unwrap())
-                    }
-                }
-                Opcode::Output { from } => result.push(from.get(self)),
-                Opcode::Halt => {
-                    self.is_finished = true;
-                    break;
-                }
-                Opcode::JumpIfTrue { check, to } => {
-                    let c = check.get(self);
-                    if c != 0 {
-                        let t = to.get(self);
-                        self.position = t as usize;
-                    } else {
-                        self.position += 3;
-                    }
-                }
-                Opcode::JumpIfFalse { check, to } => {
-                    let c = check.get(self);
-                    if c == 0 {
-                        let t = to.get(self);
-                        self.position = t as usize;
-                    } else {
-                        self.position += 3;
-                    }
+    pub fn image(&self) -> Vec<i64> {
+        self.code.image()
+    }
+
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    pub fn relative_base(&self) -> i64 {
+        self.relative_base
+    }
+
+    pub fn set_relative_base(&mut self, value: i64) {
+        self.relative_base = value;
+    }
+
+    pub fn pending_inputs(&self) -> usize {
+        self.inputs.len()
+    }
+
+    pub fn current(&self) -> Result<Opcode, VmError> {
+        Opcode::from_cells(|a| self.code.get(a), self.code.extent(), self.position)
+    }
+
+    pub fn read(&self, address: usize) -> i64 {
+        self.code.get(address)
+    }
+
+    fn decode(&mut self) -> Result<Opcode, VmError> {
+        let position = self.position;
+        if let Some(Some(opcode)) = self.decoded.get(position) {
+            return Ok(*opcode);
+        }
+        let opcode = self.current()?;
+        if position < CACHED_CELLS {
+            if self.decoded.len() <= position {
+                self.decoded.resize(position + 1, None);
+            }
+            self.decoded[position] = Some(opcode);
+        }
+        Ok(opcode)
+    }
+
+    fn write(&mut self, address: usize, value: i64) -> Result<(), VmError> {
+        let old = self.code.get(address);
+        if !self.code.set(address, value) {
+            return Err(VmError::MemoryLimit {
+                address: self.position,
+                target: address,
+                limit: self.code.limit(),
+            });
+        }
+        self.big.remove(address);
+        self.guard.written(address, old, value);
+        self.invalidate(address);
+        Ok(())
+    }
+
+    fn invalidate(&mut self, address: usize) {
+        for start in address.saturating_sub(3)..=address {
+            if let Some(cached) = self.decoded.get_mut(start) {
+                *cached = None;
+            }
+        }
+    }
+
+    fn target(&self, target: i64) -> Result<usize, VmError> {
+        if target < 0 {
+            return Err(VmError::NegativeAddress {
+                address: self.position,
+                value: target,
+            });
+        }
+        Ok(target as usize)
+    }
+
+    pub fn trace_to<P: AsRef<Path>>(&mut self, path: P) -> std::io::Result<()> {
+        self.trace = Trace(Some(Box::new(Tracer::create(path)?)));
+        Ok(())
+    }
+
+    pub fn stop_trace(&mut self) -> std::io::Result<()> {
+        match self.trace.0.take() {
+            Some(mut tracer) => tracer.flush(),
+            None => Ok(()),
+        }
+    }
+
+    fn traced<F: FnOnce(&mut Tracer)>(&mut self, f: F) {
+        if let Some(tracer) = self.trace.0.as_mut() {
+            f(tracer);
+        }
+    }
+
+    fn finish_trace(&mut self) -> Result<(), VmError> {
+        match self.trace.0.as_mut() {
+            Some(tracer) => tracer.finish().map_err(|e| VmError::TraceFailed {
+                message: e.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(value);
+    }
+
+    fn profiled(&mut self, position: usize, command: &Opcode, next: usize) {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.record(position, command, next);
+        }
+    }
+
+    pub fn step(&mut self) -> Result<StepOutcome, VmError> {
+        if self.is_finished {
+            return Ok(StepOutcome::Halted);
+        }
+        if let Some(watches) = self.watches.as_mut() {
+            if watches.take_stop() {
+                return Ok(StepOutcome::Watchpoint);
+            }
+        }
+        if self.guard.exhausted() {
+            return Ok(StepOutcome::OutOfFuel);
+        }
+        let command = self.decode()?;
+        let position = self.position;
+        if let Some(watches) = self.watches.as_mut() {
+            watches.executing(position, command.length());
+        }
+        let mut next = position + command.length();
+        self.traced(|t| t.begin(position, &command));
+
+        let outcome = match &command {
+            Opcode::Add { left, right, to } => {
+                self.combine(
+                    (left, right, to),
+                    i64::checked_add,
+                    i64::wrapping_add,
+                    |l, r| l + r,
+                )?;
+                StepOutcome::Continued
+            }
+            Opcode::Multiply { left, right, to } => {
+                self.combine(
+                    (left, right, to),
+                    i64::checked_mul,
+                    i64::wrapping_mul,
+                    |l, r| l * r,
+                )?;
+                StepOutcome::Continued
+            }
+            Opcode::Input { to } => match self.inputs.front().cloned() {
+                Some(input) => {
+                    self.traced(|t| t.input(input));
+                    to.set(self, input)?;
+                    self.inputs.pop_front();
+                    StepOutcome::Continued
                 }
-                Opcode::Less { left, right, to } => {
-                    let l = left.get(self);
-                    let r = right.get(self);
-                    if l < r {
-                        to.set(self, 1);
-                    } else {
-                        to.set(self, 0);
-                    }
+                None => {
+                    self.traced(|t| t.discard());
+                    return Ok(StepOutcome::NeedInput);
                 }
-                Opcode::Equal { left, right, to } => {
-                    let l = left.get(self);
-                    let r = right.get(self);
-                    if l == r {
-                        to.set(self, 1);
-                    } else {
-                        to.set(self, 0);
-                    }
+            },
+            Opcode::Output { from } => {
+                let output = self.output_value(from)?;
+                self.traced(|t| t.output(output));
+                StepOutcome::Output(output)
+            }
+            Opcode::Halt => {
+                self.is_finished = true;
+                self.profiled(position, &command, position);
+                self.finish_trace()?;
+                return Ok(StepOutcome::Halted);
+            }
+            Opcode::JumpIfTrue { check, to } => {
+                if self.is_nonzero(check)? {
+                    let t = to.get(self)?;
+                    next = self.target(t)?;
                 }
-                Opcode::AdjustRelativeBase { to } => {
-                    let t = to.get(self);
-                    self.relative_base = (self.relative_base as i64 + t) as usize;
+                StepOutcome::Continued
+            }
+            Opcode::JumpIfFalse { check, to } => {
+                if !self.is_nonzero(check)? {
+                    let t = to.get(self)?;
+                    next = self.target(t)?;
                 }
+                StepOutcome::Continued
+            }
+            Opcode::Less { left, right, to } => {
+                self.compare((left, right, to), i64::lt, BigInt::lt)?;
+                StepOutcome::Continued
+            }
+            Opcode::Equal { left, right, to } => {
+                self.compare((left, right, to), i64::eq, BigInt::eq)?;
+                StepOutcome::Continued
+            }
+            Opcode::AdjustRelativeBase { to } => {
+                let t = to.get(self)?;
+                let from = self.relative_base;
+                self.relative_base = from.checked_add(t).ok_or(VmError::AddressOverflow {
+                    address: self.position,
+                })?;
+                let to = self.relative_base;
+                self.traced(|t| t.relative_base(from, to));
+                StepOutcome::Continued
+            }
+        };
+        self.position = next;
+        self.profiled(position, &command, next);
+        self.finish_trace()?;
+        let io = matches!(command, Opcode::Input { .. } | Opcode::Output { .. });
+        if self.guard.executed(next, self.relative_base, io) {
+            return Ok(StepOutcome::LoopDetected);
+        }
+        Ok(outcome)
+    }
+
+    pub fn run_until<F>(&mut self, mut stop: F) -> Result<StepOutcome, VmError>
+    where
+        F: FnMut(&StepOutcome) -> bool,
+    {
+        loop {
+            let outcome = self.step()?;
+            match outcome {
+                StepOutcome::NeedInput
+                | StepOutcome::Halted
+                | StepOutcome::OutOfFuel
+                | StepOutcome::LoopDetected
+                | StepOutcome::Watchpoint => return Ok(outcome),
+                _ if stop(&outcome) => return Ok(outcome),
+                _ => (),
             }
-            if before_modifications == self.code[self.position] {
-                self.position += &command.advance_by();
+        }
+    }
+
+    pub fn run_with<I, O>(&mut self, input: &mut I, output: &mut O) -> Result<StepOutcome, VmError>
+    where
+        I: InputSource + ?Sized,
+        O: OutputSink + ?Sized,
+    {
+        if let Some(profile) = self.profile.as_mut() {
+            profile.begin_run();
+        }
+        loop {
+            match self.step()? {
+                StepOutcome::Continued => (),
+                StepOutcome::Output(value) => output.put(value),
+                StepOutcome::NeedInput => match input.next_input() {
+                    Some(value) => self.push_input(value),
+                    None => return Ok(StepOutcome::NeedInput),
+                },
+                outcome => return Ok(outcome),
             }
         }
+    }
+
+    pub fn run(&mut self, inputs: &mut Vec<i64>) -> Result<Vec<i64>, VmError> {
+        let mut result = vec![];
+        self.run_with(&mut || inputs.pop(), &mut result)?;
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        result
+    #[test]
+    fn failed_input_keeps_the_queue() {
+        let mut programm = "109,-5,203,0,99".parse::<Programm>().unwrap();
+        programm.push_input(7);
+        assert!(matches!(
+            programm.run_until(|_| false),
+            Err(VmError::NegativeAddress { .. })
+        ));
+        assert_eq!(programm.pending_inputs(), 1);
+        assert_eq!(programm.position(), 2);
     }
 }
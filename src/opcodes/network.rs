@@ -0,0 +1,131 @@
+use super::{Programm, StepOutcome, VmError};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::thread;
+
+struct Node {
+    programm: Programm,
+    input: Sender<i64>,
+    receiver: Receiver<i64>,
+    targets: Vec<usize>,
+}
+
+pub struct Report {
+    pub outputs: Vec<i64>,
+    pub result: Result<StepOutcome, VmError>,
+}
+
+impl Report {
+    pub fn halted(&self) -> bool {
+        self.result == Ok(StepOutcome::Halted)
+    }
+}
+
+#[derive(Default)]
+pub struct Network {
+    nodes: Vec<Node>,
+}
+
+impl Network {
+    pub fn new() -> Self {
+        Network { nodes: vec![] }
+    }
+
+    pub fn add(&mut self, programm: Programm) -> usize {
+        let (input, receiver) = channel();
+        self.nodes.push(Node {
+            programm,
+            input,
+            receiver,
+            targets: vec![],
+        });
+        self.nodes.len() - 1
+    }
+
+    pub fn connect(&mut self, from: usize, to: usize) {
+        self.nodes[from].targets.push(to);
+    }
+
+    pub fn send(&self, to: usize, value: i64) {
+        self.nodes[to].input.send(value).unwrap();
+    }
+
+    pub fn run(self) -> Vec<Report> {
+        let inputs = self
+            .nodes
+            .iter()
+            .map(|n| n.input.clone())
+            .collect::<Vec<Sender<i64>>>();
+        let handles = self
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let targets = node
+                    .targets
+                    .iter()
+                    .map(|t| inputs[*t].clone())
+                    .collect::<Vec<Sender<i64>>>();
+                let mut programm = node.programm;
+                let mut receiver = node.receiver;
+                thread::spawn(move || {
+                    let mut outputs = vec![];
+                    let result = programm.run_with(&mut receiver, &mut |value: i64| {
+                        outputs.push(value);
+                        for target in targets.iter() {
+                            let _ = target.send(value);
+                        }
+                    });
+                    Report {
+                        outputs,
+                        result,
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        drop(inputs);
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Reads a value, passes on its successor and stops once that reaches 10.
+    const PING: &str = "3,20,1001,20,1,20,4,20,1007,20,10,21,1005,21,0,99";
+
+    fn pair(first: &str, second: &str) -> Network {
+        let mut network = Network::new();
+        let a = network.add(first.parse().unwrap());
+        let b = network.add(second.parse().unwrap());
+        network.connect(a, b);
+        network.connect(b, a);
+        network
+    }
+
+    #[test]
+    fn two_nodes_play_ping_pong() {
+        let network = pair(PING, PING);
+        network.send(0, 0);
+        let reports = network.run();
+        assert_eq!(reports[0].outputs, vec![1, 3, 5, 7, 9, 11]);
+        assert_eq!(reports[1].outputs, vec![2, 4, 6, 8, 10]);
+        assert!(reports.iter().all(Report::halted));
+    }
+
+    #[test]
+    fn a_failing_node_closes_its_peers_input() {
+        let network = pair("3,10,4,10,98", PING);
+        network.send(0, 5);
+        let reports = network.run();
+        assert_eq!(reports[0].outputs, vec![5]);
+        assert_eq!(
+            reports[0].result,
+            Err(VmError::UnknownOpcode {
+                address: 4,
+                value: 98
+            })
+        );
+        assert_eq!(reports[1].outputs, vec![6]);
+        assert_eq!(reports[1].result, Ok(StepOutcome::NeedInput));
+    }
+}
@@ -0,0 +1,117 @@
+use std::cmp::min;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+const CHUNK: usize = 16;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Parallel {
+    threads: usize,
+    chunk: usize,
+}
+
+impl Default for Parallel {
+    fn default() -> Self {
+        Parallel {
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            chunk: CHUNK,
+        }
+    }
+}
+
+impl Parallel {
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn map<R, F>(&self, count: usize, f: F) -> Vec<R>
+    where
+        R: Send,
+        F: Fn(usize) -> R + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let results = Mutex::new(Vec::with_capacity(count));
+        thread::scope(|s| {
+            for _ in 0..self.threads {
+                s.spawn(|| loop {
+                    let start = next.fetch_add(self.chunk, Ordering::SeqCst);
+                    if start >= count {
+                        break;
+                    }
+                    let local = (start..min(start + self.chunk, count))
+                        .map(|i| (i, f(i)))
+                        .collect::<Vec<(usize, R)>>();
+                    results.lock().unwrap().extend(local);
+                });
+            }
+        });
+        let mut results = results.into_inner().unwrap();
+        results.sort_by_key(|(i, _)| *i);
+        results.into_iter().map(|(_, r)| r).collect()
+    }
+
+    pub fn find_first<R, F>(&self, count: usize, f: F) -> Option<(usize, R)>
+    where
+        R: Send,
+        F: Fn(usize) -> Option<R> + Sync,
+    {
+        let next = AtomicUsize::new(0);
+        let best = AtomicUsize::new(usize::MAX);
+        let found = Mutex::new(None);
+        thread::scope(|s| {
+            for _ in 0..self.threads {
+                s.spawn(|| loop {
+                    let start = next.fetch_add(self.chunk, Ordering::SeqCst);
+                    if start >= count || start > best.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    for i in start..min(start + self.chunk, count) {
+                        if i > best.load(Ordering::SeqCst) {
+                            break;
+                        }
+                        if let Some(r) = f(i) {
+                            best.fetch_min(i, Ordering::SeqCst);
+                            let mut found = found.lock().unwrap();
+                            if found.as_ref().is_none_or(|(j, _)| i < *j) {
+                                *found = Some((i, r));
+                            }
+                            break;
+                        }
+                    }
+                });
+            }
+        });
+        found.into_inner().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn map_keeps_index_order() {
+        let squares = Parallel::default().with_threads(4).map(1000, |i| i * i);
+        assert_eq!(squares, (0..1000).map(|i| i * i).collect::<Vec<usize>>());
+    }
+
+    #[test]
+    fn find_first_returns_the_lowest_index() {
+        for threads in 1..=8 {
+            let found = Parallel::default()
+                .with_threads(threads)
+                .find_first(1000, |i| Some(i * 2).filter(|_| i > 100 && i % 7 == 3));
+            assert_eq!(found, Some((101, 202)));
+        }
+    }
+
+    #[test]
+    fn find_first_without_a_match() {
+        let found = Parallel::default()
+            .with_threads(4)
+            .find_first(100, |_| None::<usize>);
+        assert_eq!(found, None);
+    }
+}
@@ -0,0 +1,88 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ParseMode {
+    Strict,
+    Lenient,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ParseError {
+    pub index: usize,
+    pub offset: usize,
+    pub token: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.token.is_empty() {
+            write!(f, "cell {} at byte {} is empty", self.index, self.offset)
+        } else {
+            write!(
+                f,
+                "cell {} at byte {}: `{}` is not an integer",
+                self.index, self.offset, self.token
+            )
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn strip_comments(source: &str) -> String {
+    let mut result = String::with_capacity(source.len());
+    for line in source.split_inclusive('\n') {
+        match line.find('#') {
+            Some(i) => {
+                result.push_str(&line[..i]);
+                for c in line[i..].chars() {
+                    match c {
+                        '\n' => result.push(c),
+                        _ => result.extend(std::iter::repeat_n(' ', c.len_utf8())),
+                    }
+                }
+            }
+            None => result.push_str(line),
+        }
+    }
+    result
+}
+
+fn strict(source: &str) -> Result<Vec<i64>, ParseError> {
+    let source = strip_comments(source);
+    if source.trim().is_empty() {
+        return Ok(vec![]);
+    }
+    let mut result = vec![];
+    let mut start = 0;
+    for token in source.split(',') {
+        let offset = if token.trim().is_empty() {
+            start
+        } else {
+            start + token.len() - token.trim_start().len()
+        };
+        start += token.len() + 1;
+        let token = token.trim();
+        match token.parse::<i64>() {
+            Ok(value) => result.push(value),
+            Err(_) => {
+                return Err(ParseError {
+                    index: result.len(),
+                    offset,
+                    token: token.to_string(),
+                })
+            }
+        }
+    }
+    Ok(result)
+}
+
+pub fn parse(source: &str, mode: ParseMode) -> Result<Vec<i64>, ParseError> {
+    match mode {
+        ParseMode::Strict => strict(source),
+        ParseMode::Lenient => Ok(source
+            .split(',')
+            .filter_map(|l| l.parse::<i64>().ok())
+            .collect()),
+    }
+}
@@ -0,0 +1,161 @@
+use super::disasm::Line;
+use super::{Opcode, Programm};
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Default)]
+pub struct Profile {
+    counts: HashMap<usize, u64>,
+    opcodes: HashMap<&'static str, u64>,
+    jumps: HashMap<(usize, usize), u64>,
+    runs: Vec<u64>,
+    total: u64,
+}
+
+fn sorted<K: Copy + Ord>(map: &HashMap<K, u64>) -> Vec<(K, u64)> {
+    let mut result = map.iter().map(|(k, v)| (*k, *v)).collect::<Vec<(K, u64)>>();
+    result.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    result
+}
+
+impl Profile {
+    pub(super) fn record(&mut self, address: usize, opcode: &Opcode, next: usize) {
+        *self.counts.entry(address).or_insert(0) += 1;
+        *self.opcodes.entry(opcode.mnemonic()).or_insert(0) += 1;
+        if let Opcode::JumpIfTrue { .. } | Opcode::JumpIfFalse { .. } = opcode {
+            if next != address + opcode.length() {
+                *self.jumps.entry((address, next)).or_insert(0) += 1;
+            }
+        }
+        if let Some(run) = self.runs.last_mut() {
+            *run += 1;
+        }
+        self.total += 1;
+    }
+
+    pub(super) fn begin_run(&mut self) {
+        self.runs.push(0);
+    }
+
+    pub fn count(&self, address: usize) -> u64 {
+        self.counts.get(&address).cloned().unwrap_or(0)
+    }
+
+    pub fn hot_addresses(&self) -> Vec<(usize, u64)> {
+        sorted(&self.counts)
+    }
+
+    pub fn opcodes(&self) -> Vec<(&'static str, u64)> {
+        sorted(&self.opcodes)
+    }
+
+    pub fn jumps(&self) -> Vec<((usize, usize), u64)> {
+        sorted(&self.jumps)
+    }
+
+    pub fn back_edges(&self) -> Vec<((usize, usize), u64)> {
+        self.jumps()
+            .into_iter()
+            .filter(|((from, to), _)| to <= from)
+            .collect()
+    }
+
+    pub fn report(&self, top: usize) -> String {
+        let share = |count: u64| 100.0 * count as f64 / std::cmp::max(self.total, 1) as f64;
+        let mut result = format!(
+            "instructions {} in {} runs {:?}\n\nopcodes\n",
+            self.total,
+            self.runs.len(),
+            self.runs
+        );
+        for (mnemonic, count) in self.opcodes() {
+            result.push_str(&format!(
+                "{:>6} {:>12} {:>6.2}%\n",
+                mnemonic,
+                count,
+                share(count)
+            ));
+        }
+        result.push_str("\nhot addresses\n");
+        for (address, count) in self.hot_addresses().into_iter().take(top) {
+            result.push_str(&format!(
+                "{:>6} {:>12} {:>6.2}%\n",
+                address,
+                count,
+                share(count)
+            ));
+        }
+        result.push_str("\nhot loops\n");
+        for ((from, to), count) in self.back_edges().into_iter().take(top) {
+            result.push_str(&format!("{:>6} <- {:<6} {:>12}\n", to, from, count));
+        }
+        result
+    }
+
+    pub fn annotate(&self, lines: &[Line]) -> String {
+        lines
+            .iter()
+            .map(|l| match self.count(l.address()) {
+                0 => format!("{:>12} | {}\n", "", l),
+                count => format!("{:>12} | {}\n", count, l),
+            })
+            .collect()
+    }
+}
+
+impl Programm {
+    pub fn start_profile(&mut self) {
+        self.profile = Some(Box::default());
+    }
+
+    pub fn profile(&self) -> Option<&Profile> {
+        self.profile.as_deref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::disasm;
+
+    // Counts [8] down from 3 and halts.
+    const COUNTDOWN: &str = "1001,8,-1,8,1005,8,0,99,3";
+
+    fn profiled() -> Programm {
+        let mut programm = COUNTDOWN.parse::<Programm>().unwrap();
+        programm.start_profile();
+        programm.run(&mut vec![]).unwrap();
+        programm
+    }
+
+    #[test]
+    fn counts_addresses_and_opcodes() {
+        let programm = profiled();
+        let profile = programm.profile().unwrap();
+        assert_eq!(profile.hot_addresses(), vec![(0, 3), (4, 3), (7, 1)]);
+        assert_eq!(profile.count(1), 0);
+        assert_eq!(profile.opcodes(), vec![("ADD", 3), ("JT", 3), ("HLT", 1)]);
+        assert!(profile
+            .report(10)
+            .starts_with("instructions 7 in 1 runs [7]"));
+    }
+
+    #[test]
+    fn records_taken_jumps_as_back_edges() {
+        let programm = profiled();
+        let profile = programm.profile().unwrap();
+        assert_eq!(profile.jumps(), vec![((4, 0), 2)]);
+        assert_eq!(profile.back_edges(), vec![((4, 0), 2)]);
+    }
+
+    #[test]
+    fn annotates_executed_lines() {
+        let programm = profiled();
+        let lines = disasm::linear(&programm.image());
+        let annotated = programm.profile().unwrap().annotate(&lines);
+        let counts = annotated
+            .lines()
+            .map(|l| l.split('|').next().unwrap().trim())
+            .collect::<Vec<&str>>();
+        assert_eq!(counts, vec!["3", "3", "1", ""]);
+    }
+}
@@ -0,0 +1,347 @@
+use super::memory::Memory;
+use super::watch::{Access, WatchAction};
+use super::{Arithmetic, Programm};
+use num::BigInt;
+use std::fmt::{Display, Formatter};
+use std::fs::File;
+use std::io::{BufReader, Read, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+const HEADER: &str = "intcode-snapshot";
+const VERSION: u32 = 3;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SnapshotError {
+    Io(String),
+    Version(String),
+    Format { line: usize, message: String },
+}
+
+impl SnapshotError {
+    fn format(line: usize, message: String) -> Self {
+        SnapshotError::Format { line, message }
+    }
+}
+
+impl Display for SnapshotError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SnapshotError::Io(message) => write!(f, "snapshot io failed: {}", message),
+            SnapshotError::Version(found) => write!(
+                f,
+                "unsupported snapshot version {}, expected {} {}",
+                found, HEADER, VERSION
+            ),
+            SnapshotError::Format { line, message } => write!(f, "line {}: {}", line, message),
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {}
+
+fn join(values: &[i64]) -> String {
+    values
+        .iter()
+        .map(|v| v.to_string())
+        .collect::<Vec<String>>()
+        .join(",")
+}
+
+fn split(line: usize, s: &str) -> Result<Vec<i64>, SnapshotError> {
+    if s.is_empty() {
+        return Ok(vec![]);
+    }
+    s.split(',')
+        .map(|v| {
+            v.parse::<i64>()
+                .map_err(|_| SnapshotError::format(line, format!("bad value `{}`", v)))
+        })
+        .collect()
+}
+
+fn access_name(access: Access) -> &'static str {
+    match access {
+        Access::Read => "read",
+        Access::Write => "write",
+        Access::Patch => "patch",
+    }
+}
+
+fn watchpoint(
+    line: usize,
+    s: &str,
+) -> Result<(RangeInclusive<usize>, Access, WatchAction), SnapshotError> {
+    let bad = || SnapshotError::format(line, format!("bad watch `{}`", s));
+    let words = s.split_whitespace().collect::<Vec<&str>>();
+    if words.len() != 4 {
+        return Err(bad());
+    }
+    let access = match words[0] {
+        "read" => Access::Read,
+        "write" => Access::Write,
+        "patch" => Access::Patch,
+        _ => return Err(bad()),
+    };
+    let action = match words[3] {
+        "notify" => WatchAction::Notify,
+        "stop" => WatchAction::Stop,
+        _ => return Err(bad()),
+    };
+    let start = number::<usize>(line, "watch", words[1])?;
+    let end = number::<usize>(line, "watch", words[2])?;
+    Ok((start..=end, access, action))
+}
+
+fn ranges(line: usize, s: &str) -> Result<Vec<RangeInclusive<usize>>, SnapshotError> {
+    s.split(',')
+        .filter(|r| !r.is_empty())
+        .map(|r| {
+            let (start, end) = r.split_once('-').unwrap_or((r, r));
+            let (start, end) = (number(line, "range", start)?, number(line, "range", end)?);
+            if end < start {
+                return Err(SnapshotError::format(line, format!("bad range `{}`", r)));
+            }
+            Ok(start..=end)
+        })
+        .collect()
+}
+
+fn number<T: std::str::FromStr>(line: usize, key: &str, s: &str) -> Result<T, SnapshotError> {
+    s.parse::<T>()
+        .map_err(|_| SnapshotError::format(line, format!("bad {} `{}`", key, s)))
+}
+
+impl Programm {
+    // Outputs belong to whoever took them from `step` and are not saved, nor are
+    // trace files, profiles and watch hits that were not taken yet.
+    pub fn snapshot(&self) -> String {
+        let mut result = format!("{} {}\n", HEADER, VERSION);
+        result.push_str(&format!("position {}\n", self.position));
+        result.push_str(&format!("relative_base {}\n", self.relative_base));
+        result.push_str(&format!("finished {}\n", self.is_finished));
+        result.push_str(&format!("limit {}\n", self.code.limit()));
+        result.push_str(&format!("extent {}\n", self.code.extent()));
+        result.push_str(&format!("arithmetic {}\n", self.arithmetic.name()));
+        if let Some(fuel) = self.fuel() {
+            result.push_str(&format!("fuel {}\n", fuel));
+        }
+        result.push_str(&format!("detect_loops {}\n", self.detects_loops()));
+        let inputs = self.inputs.iter().cloned().collect::<Vec<i64>>();
+        result.push_str(&format!("inputs {}\n", join(&inputs)));
+        for point in self.watchpoints() {
+            let action = match point.action {
+                WatchAction::Notify => "notify",
+                WatchAction::Stop => "stop",
+            };
+            result.push_str(&format!(
+                "watch {} {} {} {}\n",
+                access_name(point.access),
+                point.range.start(),
+                point.range.end(),
+                action
+            ));
+        }
+        if let Some(watches) = self.watches.as_ref() {
+            let executed = watches
+                .executed_ranges()
+                .iter()
+                .map(|r| format!("{}-{}", r.start(), r.end()))
+                .collect::<Vec<String>>();
+            if !executed.is_empty() {
+                result.push_str(&format!("executed {}\n", executed.join(",")));
+            }
+        }
+        for (address, cells) in self.code.pages() {
+            let used = cells.iter().rposition(|v| *v != 0).map_or(0, |i| i + 1);
+            if used > 0 {
+                result.push_str(&format!("page {} {}\n", address, join(&cells[..used])));
+            }
+        }
+        let mut big = self.big.cells.iter().collect::<Vec<(&usize, &BigInt)>>();
+        big.sort_by_key(|(address, _)| **address);
+        for (address, value) in big {
+            result.push_str(&format!("big {} {}\n", address, value));
+        }
+        result
+    }
+
+    pub fn restore(snapshot: &str) -> Result<Programm, SnapshotError> {
+        let mut lines = snapshot.lines().enumerate().map(|(i, l)| (i + 1, l.trim()));
+        let version = |header: &str| {
+            header
+                .strip_prefix(HEADER)
+                .and_then(|v| v.trim().parse::<u32>().ok())
+        };
+        match lines.next() {
+            Some((_, header)) if matches!(version(header), Some(1..=VERSION)) => (),
+            Some((_, header)) => return Err(SnapshotError::Version(header.to_string())),
+            None => return Err(SnapshotError::format(1, "empty snapshot".to_string())),
+        }
+        let mut programm = Programm::from(vec![]);
+        let mut pages = vec![];
+        let mut big = vec![];
+        let mut extent = None;
+        let mut fuel = None;
+        let mut detect_loops = false;
+        let mut watches = vec![];
+        let mut executed = (0, vec![]);
+        for (line, text) in lines.filter(|(_, l)| !l.is_empty()) {
+            let (key, value) = text.split_once(' ').unwrap_or((text, ""));
+            match key {
+                "position" => programm.position = number(line, key, value)?,
+                "relative_base" => programm.relative_base = number(line, key, value)?,
+                "finished" => programm.is_finished = number(line, key, value)?,
+                "limit" => programm.code.set_limit(number(line, key, value)?),
+                "extent" => extent = Some(number(line, key, value)?),
+                "arithmetic" => {
+                    programm.arithmetic = value
+                        .parse::<Arithmetic>()
+                        .map_err(|e| SnapshotError::format(line, e))?
+                }
+                "big" => {
+                    let (address, value) = value.split_once(' ').unwrap_or((value, ""));
+                    big.push((
+                        number::<usize>(line, key, address)?,
+                        number::<BigInt>(line, key, value)?,
+                    ));
+                }
+                "fuel" => fuel = Some(number(line, key, value)?),
+                "detect_loops" => detect_loops = number(line, key, value)?,
+                "watch" => watches.push(watchpoint(line, value)?),
+                "executed" => executed = (line, ranges(line, value)?),
+                "inputs" => programm.inputs = split(line, value)?.into_iter().collect(),
+                "page" => {
+                    let (address, cells) = value.split_once(' ').unwrap_or((value, ""));
+                    pages.push((line, number::<usize>(line, key, address)?, split(line, cells)?));
+                }
+                _ => {
+                    return Err(SnapshotError::format(
+                        line,
+                        format!("unknown field `{}`", key),
+                    ))
+                }
+            }
+        }
+        let mut memory = Memory::with_limit(programm.code.limit());
+        for (line, address, cells) in pages {
+            for (i, value) in cells.into_iter().enumerate() {
+                let target = address.checked_add(i).ok_or_else(|| {
+                    SnapshotError::format(line, format!("page {} is out of range", address))
+                })?;
+                if !memory.set(target, value) {
+                    return Err(SnapshotError::format(
+                        line,
+                        format!("page {} exceeds the memory limit", address),
+                    ));
+                }
+            }
+        }
+        // Without an extent, or with one short of the pages, decode what was saved.
+        let extent = extent.map_or(memory.extent(), |e: usize| e.max(memory.extent()));
+        memory.set_extent(extent);
+        programm.big.cells.extend(big);
+        if let Some(fuel) = fuel {
+            programm = programm.with_fuel(fuel);
+        }
+        if detect_loops {
+            programm = programm.with_loop_detection();
+        }
+        for (range, access, action) in watches {
+            programm.watch(range, access, action);
+        }
+        let (line, executed) = executed;
+        if let Some(range) = executed
+            .iter()
+            .find(|r| *r.end() >= extent || !memory.covers(r))
+        {
+            return Err(SnapshotError::format(
+                line,
+                format!("executed range {:?} is beyond the saved cells", range),
+            ));
+        }
+        programm.code = memory;
+        if let Some(watches) = programm.watches.as_mut() {
+            for range in executed {
+                watches.executing(*range.start(), range.end() - range.start() + 1);
+            }
+        }
+        Ok(programm)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), SnapshotError> {
+        File::create(path)
+            .and_then(|mut f| f.write_all(self.snapshot().as_bytes()))
+            .map_err(|e| SnapshotError::Io(e.to_string()))
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Programm, SnapshotError> {
+        let mut buffer = String::new();
+        File::open(path)
+            .and_then(|f| BufReader::new(f).read_to_string(&mut buffer))
+            .map_err(|e| SnapshotError::Io(e.to_string()))?;
+        Programm::restore(&buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::StepOutcome;
+
+    // Moves the relative base, then echoes two inputs and prints their sum.
+    const ECHO_SUM: &str = "109,100,203,0,204,0,203,1,204,1,22201,0,1,2,204,2,99";
+
+    fn started() -> Programm {
+        let mut programm = ECHO_SUM.parse::<Programm>().unwrap();
+        programm.push_input(5);
+        programm.push_input(6);
+        programm
+    }
+
+    #[test]
+    fn resumes_where_it_was_saved() {
+        let expected = started().run(&mut vec![]).unwrap();
+        let mut programm = started();
+        assert_eq!(
+            programm.run_until(|o| o != &StepOutcome::Continued),
+            Ok(StepOutcome::Output(5))
+        );
+        let text = programm.snapshot();
+        let mut restored = Programm::restore(&text).unwrap();
+        assert_eq!(restored.relative_base(), 100);
+        assert_eq!(restored.pending_inputs(), 1);
+        assert_eq!(restored.snapshot(), text);
+        let mut outputs = vec![5];
+        outputs.extend(restored.run(&mut vec![]).unwrap());
+        assert_eq!(outputs, expected);
+    }
+
+    #[test]
+    fn missing_extent_covers_the_pages() {
+        let text = "intcode-snapshot 3\npage 0 104,7,99\n";
+        let mut programm = Programm::restore(text).unwrap();
+        assert_eq!(programm.image(), vec![104, 7, 99]);
+        assert_eq!(programm.run(&mut vec![]), Ok(vec![7]));
+    }
+
+    #[test]
+    fn executed_ranges_stay_within_saved_pages() {
+        let text = "intcode-snapshot 3\nextent 99999999999\nwatch patch 0 10 stop\n\
+                    executed 0-99999999998\npage 0 104,7,99\n";
+        assert!(matches!(
+            Programm::restore(text),
+            Err(SnapshotError::Format { line: 4, .. })
+        ));
+        let text = text.replace("0-99999999998", "0-2");
+        assert!(Programm::restore(&text).is_ok());
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        assert!(matches!(
+            Programm::restore("intcode-snapshot 99\n"),
+            Err(SnapshotError::Version(_))
+        ));
+    }
+}
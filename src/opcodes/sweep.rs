@@ -0,0 +1,252 @@
+use super::parallel::Parallel;
+use super::{IterInput, Programm, StepOutcome, VmError};
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Param {
+    Memory(usize),
+    Input,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum SweepError {
+    TooLarge,
+}
+
+impl Display for SweepError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SweepError::TooLarge => write!(f, "sweep has more combinations than fit in usize"),
+        }
+    }
+}
+
+impl std::error::Error for SweepError {}
+
+fn size(range: &RangeInclusive<i64>) -> Option<usize> {
+    if range.is_empty() {
+        return Some(0);
+    }
+    usize::try_from(*range.end() as i128 - *range.start() as i128 + 1).ok()
+}
+
+pub struct Run {
+    pub values: Vec<i64>,
+    pub outputs: Vec<i64>,
+    pub result: Result<StepOutcome, VmError>,
+    pub programm: Programm,
+}
+
+#[derive(Clone)]
+pub struct Sweep {
+    base: Programm,
+    params: Vec<(Param, RangeInclusive<i64>)>,
+    fuel: Option<u64>,
+}
+
+impl Sweep {
+    pub fn new(base: &Programm) -> Self {
+        Sweep {
+            base: base.clone(),
+            params: vec![],
+            fuel: None,
+        }
+    }
+
+    pub fn patch(mut self, address: usize, range: RangeInclusive<i64>) -> Self {
+        self.params.push((Param::Memory(address), range));
+        self
+    }
+
+    pub fn input(mut self, range: RangeInclusive<i64>) -> Self {
+        self.params.push((Param::Input, range));
+        self
+    }
+
+    pub fn with_fuel(mut self, fuel: u64) -> Self {
+        self.fuel = Some(fuel);
+        self
+    }
+
+    pub fn count(&self) -> Result<usize, SweepError> {
+        self.params.iter().try_fold(1usize, |total, (_, range)| {
+            size(range)
+                .and_then(|s| total.checked_mul(s))
+                .ok_or(SweepError::TooLarge)
+        })
+    }
+
+    pub fn combination(&self, mut index: usize) -> Vec<i64> {
+        let mut result = vec![0; self.params.len()];
+        for (i, (_, range)) in self.params.iter().enumerate().rev() {
+            let size = size(range).unwrap();
+            result[i] = range.start() + (index % size) as i64;
+            index /= size;
+        }
+        result
+    }
+
+    pub fn run_one(&self, values: &[i64]) -> Run {
+        let mut programm = self.base.clone();
+        if let Some(fuel) = self.fuel {
+            programm = programm.with_fuel(fuel);
+        }
+        let mut inputs = vec![];
+        let mut result = Ok(StepOutcome::Continued);
+        for ((param, _), value) in self.params.iter().zip(values) {
+            match param {
+                Param::Memory(address) => {
+                    if let Err(e) = programm.alter(*address, *value) {
+                        result = Err(e);
+                    }
+                }
+                Param::Input => inputs.push(*value),
+            }
+        }
+        let mut outputs = vec![];
+        if result.is_ok() {
+            result = programm.run_with(&mut IterInput(inputs.into_iter()), &mut outputs);
+        }
+        Run {
+            values: values.to_vec(),
+            outputs,
+            result,
+            programm,
+        }
+    }
+
+    pub fn find_all<P: Fn(&Run) -> bool>(&self, predicate: P) -> Result<Vec<Run>, SweepError> {
+        Ok((0..self.count()?)
+            .map(|i| self.run_one(&self.combination(i)))
+            .filter(|r| predicate(r))
+            .collect())
+    }
+
+    pub fn par_find_all<P>(&self, parallel: &Parallel, predicate: P) -> Result<Vec<Run>, SweepError>
+    where
+        P: Fn(&Run) -> bool + Sync,
+    {
+        Ok(parallel
+            .map(self.count()?, |i| {
+                Some(self.run_one(&self.combination(i))).filter(|r| predicate(r))
+            })
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    pub fn par_find_first<P>(
+        &self,
+        parallel: &Parallel,
+        predicate: P,
+    ) -> Result<Option<Run>, SweepError>
+    where
+        P: Fn(&Run) -> bool + Sync,
+    {
+        Ok(parallel
+            .find_first(self.count()?, |i| {
+                Some(self.run_one(&self.combination(i))).filter(|r| predicate(r))
+            })
+            .map(|(_, r)| r))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sweep(code: &str) -> Sweep {
+        Sweep::new(&code.parse::<Programm>().unwrap())
+    }
+
+    #[test]
+    fn combinations_vary_the_last_parameter_fastest() {
+        let sweep = sweep("99").patch(1, 0..=2).input(5..=6);
+        assert_eq!(sweep.count(), Ok(6));
+        assert_eq!(sweep.combination(0), vec![0, 5]);
+        assert_eq!(sweep.combination(1), vec![0, 6]);
+        assert_eq!(sweep.combination(5), vec![2, 6]);
+    }
+
+    #[test]
+    fn finds_patches_in_order() {
+        let found = sweep("1101,0,0,0,99")
+            .patch(1, 0..=9)
+            .patch(2, 0..=9)
+            .find_all(|r| r.programm.read(0) == 12)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.values)
+            .collect::<Vec<Vec<i64>>>();
+        assert_eq!(
+            found,
+            vec![
+                vec![3, 9],
+                vec![4, 8],
+                vec![5, 7],
+                vec![6, 6],
+                vec![7, 5],
+                vec![8, 4],
+                vec![9, 3]
+            ]
+        );
+    }
+
+    #[test]
+    fn feeds_inputs() {
+        let outputs = sweep("3,0,1002,0,3,0,4,0,99")
+            .input(1..=4)
+            .find_all(|_| true)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.outputs)
+            .collect::<Vec<Vec<i64>>>();
+        assert_eq!(outputs, vec![vec![3], vec![6], vec![9], vec![12]]);
+    }
+
+    #[test]
+    fn fuel_stops_spinning_candidates() {
+        let results = sweep("1105,0,0,99")
+            .patch(1, 0..=1)
+            .with_fuel(100)
+            .find_all(|_| true)
+            .unwrap()
+            .into_iter()
+            .map(|r| r.result)
+            .collect::<Vec<Result<StepOutcome, VmError>>>();
+        assert_eq!(
+            results,
+            vec![Ok(StepOutcome::Halted), Ok(StepOutcome::OutOfFuel)]
+        );
+    }
+
+    #[test]
+    fn parallel_search_matches_sequential() {
+        let sweep = sweep("1101,0,0,0,99").patch(1, 0..=30).patch(2, 0..=30);
+        let parallel = Parallel::default().with_threads(4);
+        let values = |runs: Result<Vec<Run>, SweepError>| {
+            runs.unwrap()
+                .into_iter()
+                .map(|r| r.values)
+                .collect::<Vec<_>>()
+        };
+        let sequential = values(sweep.find_all(|r| r.programm.read(0) % 9 == 4));
+        let found = values(sweep.par_find_all(&parallel, |r| r.programm.read(0) % 9 == 4));
+        assert_eq!(found, sequential);
+        let first = sweep.par_find_first(&parallel, |r| r.programm.read(0) == 40);
+        assert_eq!(first.unwrap().map(|r| r.values), Some(vec![10, 30]));
+    }
+
+    #[test]
+    fn rejects_sweeps_too_large_to_count() {
+        let huge = sweep("99").input(i64::MIN..=i64::MAX);
+        assert_eq!(huge.count(), Err(SweepError::TooLarge));
+        let huge = sweep("99").patch(1, 0..=i64::MAX).patch(2, 0..=2);
+        assert_eq!(huge.count(), Err(SweepError::TooLarge));
+        assert!(huge.find_all(|_| true).is_err());
+        let empty = sweep("99").patch(1, RangeInclusive::new(5, 4)).input(0..=9);
+        assert_eq!(empty.count(), Ok(0));
+    }
+}
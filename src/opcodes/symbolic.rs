@@ -0,0 +1,542 @@
+use super::{Mode, Opcode, Programm, VmError};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Expr {
+    constant: i64,
+    terms: BTreeMap<String, i64>,
+}
+
+impl Expr {
+    pub fn constant(value: i64) -> Self {
+        Expr {
+            constant: value,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn symbol(name: &str) -> Self {
+        let mut terms = BTreeMap::new();
+        terms.insert(name.to_string(), 1);
+        Expr { constant: 0, terms }
+    }
+
+    pub fn as_constant(&self) -> Option<i64> {
+        if self.terms.is_empty() {
+            Some(self.constant)
+        } else {
+            None
+        }
+    }
+
+    fn add(&self, other: &Expr) -> Option<Expr> {
+        let mut result = self.clone();
+        result.constant = result.constant.checked_add(other.constant)?;
+        for (name, k) in other.terms.iter() {
+            let sum = result.terms.get(name).unwrap_or(&0).checked_add(*k)?;
+            if sum == 0 {
+                result.terms.remove(name);
+            } else {
+                result.terms.insert(name.clone(), sum);
+            }
+        }
+        Some(result)
+    }
+
+    fn scale(&self, factor: i64) -> Option<Expr> {
+        if factor == 0 {
+            return Some(Expr::constant(0));
+        }
+        let mut terms = BTreeMap::new();
+        for (name, k) in self.terms.iter() {
+            terms.insert(name.clone(), k.checked_mul(factor)?);
+        }
+        Some(Expr {
+            constant: self.constant.checked_mul(factor)?,
+            terms,
+        })
+    }
+
+    fn multiply(&self, other: &Expr) -> Option<Expr> {
+        match (self.as_constant(), other.as_constant()) {
+            (Some(k), _) => other.scale(k),
+            (_, Some(k)) => self.scale(k),
+            _ => None,
+        }
+    }
+
+    pub fn solve(
+        &self,
+        target: i64,
+        domains: &[(&str, RangeInclusive<i64>)],
+    ) -> Result<Vec<BTreeMap<String, i64>>, String> {
+        if let Some(name) = self
+            .terms
+            .keys()
+            .find(|n| !domains.iter().any(|(d, _)| d == n))
+        {
+            return Err(format!("no domain given for `{}`", name));
+        }
+        let solved = domains
+            .iter()
+            .rposition(|(name, _)| self.terms.contains_key(*name));
+        let mut result = vec![];
+        let mut assignment = BTreeMap::new();
+        self.enumerate(target, domains, solved, 0, &mut assignment, &mut result);
+        Ok(result)
+    }
+
+    fn enumerate(
+        &self,
+        target: i64,
+        domains: &[(&str, RangeInclusive<i64>)],
+        solved: Option<usize>,
+        index: usize,
+        assignment: &mut BTreeMap<String, i64>,
+        result: &mut Vec<BTreeMap<String, i64>>,
+    ) {
+        if index == domains.len() {
+            if self.evaluate(assignment) == Some(target) {
+                result.push(assignment.clone());
+            }
+            return;
+        }
+        let (name, range) = &domains[index];
+        if Some(index) == solved {
+            let k = self.terms[*name];
+            let rest = assignment.iter().try_fold(self.constant, |acc, (n, v)| {
+                acc.checked_add(self.terms.get(n).unwrap_or(&0).checked_mul(*v)?)
+            });
+            if let Some(value) = rest.and_then(|r| target.checked_sub(r)) {
+                let quotient = match value.checked_rem(k) {
+                    Some(0) => value.checked_div(k),
+                    _ => None,
+                };
+                if let Some(quotient) = quotient.filter(|q| range.contains(q)) {
+                    assignment.insert(name.to_string(), quotient);
+                    self.enumerate(target, domains, solved, index + 1, assignment, result);
+                    assignment.remove(*name);
+                }
+            }
+            return;
+        }
+        for value in range.clone() {
+            assignment.insert(name.to_string(), value);
+            self.enumerate(target, domains, solved, index + 1, assignment, result);
+        }
+        assignment.remove(*name);
+    }
+
+    pub fn evaluate(&self, assignment: &BTreeMap<String, i64>) -> Option<i64> {
+        self.terms.iter().try_fold(self.constant, |acc, (name, k)| {
+            acc.checked_add(k.checked_mul(*assignment.get(name)?)?)
+        })
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut first = true;
+        for (name, k) in self.terms.iter() {
+            let sign = if *k < 0 { "-" } else { "+" };
+            match (first, k.unsigned_abs()) {
+                (true, 1) if *k < 0 => write!(f, "-{}", name)?,
+                (true, 1) => write!(f, "{}", name)?,
+                (true, _) => write!(f, "{}*{}", k, name)?,
+                (false, 1) => write!(f, " {} {}", sign, name)?,
+                (false, a) => write!(f, " {} {}*{}", sign, a, name)?,
+            }
+            first = false;
+        }
+        match (first, self.constant) {
+            (true, c) => write!(f, "{}", c),
+            (false, 0) => Ok(()),
+            (false, c) if c < 0 => write!(f, " - {}", c.unsigned_abs()),
+            (false, c) => write!(f, " + {}", c),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Value {
+    Linear(Expr),
+    Opaque(String),
+}
+
+impl Value {
+    fn constant(&self) -> Option<i64> {
+        match self {
+            Value::Linear(e) => e.as_constant(),
+            Value::Opaque(_) => None,
+        }
+    }
+}
+
+impl Display for Value {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Linear(e) => write!(f, "{}", e),
+            Value::Opaque(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum SymbolicError {
+    Vm(VmError),
+    SymbolicBranch { address: usize, condition: String },
+    SymbolicAddress { address: usize, target: String },
+    SymbolicCode { address: usize },
+    MissingInput { address: usize },
+    StepLimit { steps: usize },
+}
+
+impl Display for SymbolicError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SymbolicError::Vm(e) => write!(f, "{}", e),
+            SymbolicError::SymbolicBranch { address, condition } => write!(
+                f,
+                "branch at address {} depends on symbolic value {}",
+                address, condition
+            ),
+            SymbolicError::SymbolicAddress { address, target } => write!(
+                f,
+                "instruction at address {} uses symbolic value {} as an address",
+                address, target
+            ),
+            SymbolicError::SymbolicCode { address } => {
+                write!(f, "instruction at address {} is symbolic", address)
+            }
+            SymbolicError::MissingInput { address } => {
+                write!(f, "instruction at address {} needs more input", address)
+            }
+            SymbolicError::StepLimit { steps } => {
+                write!(f, "no halt within {} steps", steps)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SymbolicError {}
+
+impl From<VmError> for SymbolicError {
+    fn from(e: VmError) -> Self {
+        SymbolicError::Vm(e)
+    }
+}
+
+pub struct Symbolic {
+    base: Programm,
+    cells: HashMap<usize, Value>,
+    position: usize,
+    relative_base: i64,
+    inputs: VecDeque<Value>,
+    outputs: Vec<Value>,
+}
+
+impl Symbolic {
+    pub fn new(programm: &Programm) -> Self {
+        Symbolic {
+            base: programm.clone(),
+            cells: HashMap::new(),
+            position: programm.position,
+            relative_base: programm.relative_base,
+            inputs: programm.inputs.iter().map(|v| Value::Linear(Expr::constant(*v))).collect(),
+            outputs: vec![],
+        }
+    }
+
+    pub fn symbol_at(&mut self, address: usize, name: &str) {
+        self.cells.insert(address, Value::Linear(Expr::symbol(name)));
+    }
+
+    pub fn push_input(&mut self, value: i64) {
+        self.inputs.push_back(Value::Linear(Expr::constant(value)));
+    }
+
+    pub fn push_symbol(&mut self, name: &str) {
+        self.inputs.push_back(Value::Linear(Expr::symbol(name)));
+    }
+
+    pub fn memory(&self, address: usize) -> Value {
+        match self.cells.get(&address) {
+            Some(value) => value.clone(),
+            None => Value::Linear(Expr::constant(self.base.read(address))),
+        }
+    }
+
+    pub fn outputs(&self) -> &[Value] {
+        &self.outputs
+    }
+
+    fn concrete(&self, address: usize) -> Option<i64> {
+        match self.cells.get(&address) {
+            Some(value) => value.constant(),
+            None => Some(self.base.read(address)),
+        }
+    }
+
+    fn address(&self, n: usize, mode: &Mode) -> Result<usize, SymbolicError> {
+        let cell = self.position + n;
+        let symbolic = |target: String| SymbolicError::SymbolicAddress {
+            address: self.position,
+            target,
+        };
+        let offset = match self.concrete(cell) {
+            Some(offset) => offset,
+            None => return Err(symbolic(self.memory(cell).to_string())),
+        };
+        let overflow = SymbolicError::Vm(VmError::AddressOverflow {
+            address: self.position,
+        });
+        let target = match mode {
+            Mode::Relative(_) => self.relative_base.checked_add(offset).ok_or(overflow)?,
+            _ => offset,
+        };
+        if target < 0 {
+            return Err(SymbolicError::Vm(VmError::NegativeAddress {
+                address: self.position,
+                value: target,
+            }));
+        }
+        Ok(target as usize)
+    }
+
+    fn get(&self, n: usize, mode: &Mode) -> Result<Value, SymbolicError> {
+        match mode {
+            Mode::Immediate(_) => Ok(self.memory(self.position + n)),
+            _ => match self.address(n, mode) {
+                Ok(target) => Ok(self.memory(target)),
+                Err(SymbolicError::SymbolicAddress { target, .. }) => {
+                    Ok(Value::Opaque(format!("mem[{}]", target)))
+                }
+                Err(e) => Err(e),
+            },
+        }
+    }
+
+    fn set(&mut self, n: usize, mode: &Mode, value: Value) -> Result<(), SymbolicError> {
+        if let Mode::Immediate(_) = mode {
+            return Err(SymbolicError::Vm(VmError::WriteToImmediate {
+                address: self.position,
+            }));
+        }
+        let target = self.address(n, mode)?;
+        self.cells.insert(target, value);
+        Ok(())
+    }
+
+    fn decode(&self) -> Result<Opcode, SymbolicError> {
+        if self.concrete(self.position).is_none() {
+            return Err(SymbolicError::SymbolicCode {
+                address: self.position,
+            });
+        }
+        let cell = |a: usize| self.concrete(a).unwrap_or(0);
+        Ok(Opcode::from_cells(cell, usize::MAX, self.position)?)
+    }
+
+    fn combine(
+        left: Value,
+        right: Value,
+        symbol: &str,
+        linear: fn(&Expr, &Expr) -> Option<Expr>,
+        concrete: fn(i64, i64) -> i64,
+    ) -> Value {
+        // Known values wrap like the VM's default arithmetic.
+        if let (Some(l), Some(r)) = (left.constant(), right.constant()) {
+            return Value::Linear(Expr::constant(concrete(l, r)));
+        }
+        match (&left, &right) {
+            (Value::Linear(l), Value::Linear(r)) => match linear(l, r) {
+                Some(e) => Value::Linear(e),
+                None => Value::Opaque(format!("({}) {} ({})", l, symbol, r)),
+            },
+            _ => Value::Opaque(format!("({}) {} ({})", left, symbol, right)),
+        }
+    }
+
+    fn compare(left: Value, right: Value, symbol: &str, test: fn(i64) -> bool) -> Value {
+        if let (Some(l), Some(r)) = (left.constant(), right.constant()) {
+            return Value::Linear(Expr::constant(if test(l.cmp(&r) as i64) { 1 } else { 0 }));
+        }
+        let difference = match (&left, &right) {
+            (Value::Linear(l), Value::Linear(r)) => r.scale(-1).and_then(|r| l.add(&r)),
+            _ => None,
+        };
+        match difference.and_then(|d| d.as_constant()) {
+            Some(d) => Value::Linear(Expr::constant(if test(d) { 1 } else { 0 })),
+            None => Value::Opaque(format!("({}) {} ({})", left, symbol, right)),
+        }
+    }
+
+    pub fn step(&mut self) -> Result<bool, SymbolicError> {
+        let command = self.decode()?;
+        let mut next = self.position + command.length();
+        match &command {
+            Opcode::Add { left, right, to } => {
+                let value = Self::combine(
+                    self.get(1, left)?,
+                    self.get(2, right)?,
+                    "+",
+                    Expr::add,
+                    i64::wrapping_add,
+                );
+                self.set(3, to, value)?;
+            }
+            Opcode::Multiply { left, right, to } => {
+                let value = Self::combine(
+                    self.get(1, left)?,
+                    self.get(2, right)?,
+                    "*",
+                    Expr::multiply,
+                    i64::wrapping_mul,
+                );
+                self.set(3, to, value)?;
+            }
+            Opcode::Less { left, right, to } => {
+                let value = Self::compare(self.get(1, left)?, self.get(2, right)?, "<", |d| d < 0);
+                self.set(3, to, value)?;
+            }
+            Opcode::Equal { left, right, to } => {
+                let value =
+                    Self::compare(self.get(1, left)?, self.get(2, right)?, "==", |d| d == 0);
+                self.set(3, to, value)?;
+            }
+            Opcode::Input { to } => {
+                let value = self.inputs.pop_front().ok_or(SymbolicError::MissingInput {
+                    address: self.position,
+                })?;
+                self.set(1, to, value)?;
+            }
+            Opcode::Output { from } => {
+                let value = self.get(1, from)?;
+                self.outputs.push(value);
+            }
+            Opcode::JumpIfTrue { check, to } | Opcode::JumpIfFalse { check, to } => {
+                let check = self.get(1, check)?;
+                let c = check.constant().ok_or(SymbolicError::SymbolicBranch {
+                    address: self.position,
+                    condition: check.to_string(),
+                })?;
+                if (c != 0) == matches!(command, Opcode::JumpIfTrue { .. }) {
+                    let target = self.get(2, to)?;
+                    next = match target.constant() {
+                        Some(t) if t >= 0 => t as usize,
+                        Some(t) => {
+                            return Err(SymbolicError::Vm(VmError::NegativeAddress {
+                                address: self.position,
+                                value: t,
+                            }))
+                        }
+                        None => {
+                            return Err(SymbolicError::SymbolicAddress {
+                                address: self.position,
+                                target: target.to_string(),
+                            })
+                        }
+                    };
+                }
+            }
+            Opcode::AdjustRelativeBase { to } => {
+                let value = self.get(1, to)?;
+                let offset = value.constant().ok_or(SymbolicError::SymbolicAddress {
+                    address: self.position,
+                    target: value.to_string(),
+                })?;
+                let overflow = SymbolicError::Vm(VmError::AddressOverflow {
+                    address: self.position,
+                });
+                self.relative_base = self.relative_base.checked_add(offset).ok_or(overflow)?;
+            }
+            Opcode::Halt => return Ok(false),
+        }
+        self.position = next;
+        Ok(true)
+    }
+
+    pub fn run(&mut self, steps: usize) -> Result<(), SymbolicError> {
+        for _ in 0..steps {
+            if !self.step()? {
+                return Ok(());
+            }
+        }
+        Err(SymbolicError::StepLimit { steps })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn symbolic(code: &str) -> Symbolic {
+        Symbolic::new(&code.parse::<Programm>().unwrap())
+    }
+
+    #[test]
+    fn solves_a_noun_verb_formula() {
+        let mut machine = symbolic("1102,0,100,9,1001,9,0,0,99,0");
+        machine.symbol_at(1, "noun");
+        machine.symbol_at(6, "verb");
+        machine.run(10).unwrap();
+        let result = match machine.memory(0) {
+            Value::Linear(e) => e,
+            Value::Opaque(s) => panic!("opaque result {}", s),
+        };
+        assert_eq!(result.to_string(), "100*noun + verb");
+        let solutions = result
+            .solve(1202, &[("noun", 0..=99), ("verb", 0..=99)])
+            .unwrap();
+        assert_eq!(solutions.len(), 1);
+        assert_eq!(solutions[0]["noun"], 12);
+        assert_eq!(solutions[0]["verb"], 2);
+    }
+
+    #[test]
+    fn relative_base_overflow_is_an_error() {
+        let overflow = Err(SymbolicError::Vm(VmError::AddressOverflow { address: 2 }));
+        assert_eq!(
+            symbolic("109,9223372036854775807,109,1,99").run(10),
+            overflow
+        );
+        assert_eq!(
+            symbolic("109,9223372036854775807,204,1,99").run(10),
+            overflow
+        );
+    }
+
+    #[test]
+    fn known_values_wrap_like_the_vm() {
+        for code in &[
+            "1101,9223372036854775807,1,11,1005,11,8,99,104,1,99,0",
+            "1107,-9223372036854775808,1,11,1005,11,8,99,104,1,99,0",
+        ] {
+            let mut machine = symbolic(code);
+            assert_eq!(machine.run(10), Ok(()));
+            assert_eq!(
+                machine.outputs(),
+                &[Value::Linear(Expr::constant(1))],
+                "{}",
+                code
+            );
+            let mut vm = code.parse::<Programm>().unwrap();
+            assert_eq!(vm.run(&mut vec![]), Ok(vec![1]));
+        }
+    }
+
+    #[test]
+    fn unrepresentable_quotient_has_no_solution() {
+        let negated = Expr::symbol("x").scale(-1).unwrap();
+        assert_eq!(
+            negated.solve(i64::MIN, &[("x", i64::MIN..=i64::MAX)]),
+            Ok(vec![])
+        );
+        let mut terms = BTreeMap::new();
+        terms.insert("x".to_string(), i64::MIN);
+        let extreme = Expr { constant: 0, terms };
+        assert_eq!(extreme.to_string(), "-9223372036854775808*x");
+    }
+}
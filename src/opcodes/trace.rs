@@ -0,0 +1,168 @@
+use super::Opcode;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+struct Entry {
+    address: usize,
+    instruction: String,
+    operands: Vec<i64>,
+    writes: Vec<(usize, i64)>,
+    relative_base: Option<(i64, i64)>,
+    input: Option<i64>,
+    output: Option<i64>,
+}
+
+pub struct Tracer {
+    out: BufWriter<File>,
+    step: u64,
+    entry: Option<Entry>,
+}
+
+#[derive(Default)]
+pub struct Trace(pub(super) Option<Box<Tracer>>);
+
+impl Clone for Trace {
+    fn clone(&self) -> Self {
+        Trace(None)
+    }
+}
+
+fn optional(value: Option<i64>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_else(|| "null".to_string())
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl Tracer {
+    pub fn create<P: AsRef<Path>>(path: P) -> std::io::Result<Self> {
+        Ok(Tracer {
+            out: BufWriter::new(File::create(path)?),
+            step: 0,
+            entry: None,
+        })
+    }
+
+    pub(super) fn begin(&mut self, address: usize, opcode: &Opcode) {
+        self.entry = Some(Entry {
+            address,
+            instruction: opcode.to_string(),
+            operands: vec![],
+            writes: vec![],
+            relative_base: None,
+            input: None,
+            output: None,
+        });
+    }
+
+    fn entry(&mut self) -> Option<&mut Entry> {
+        self.entry.as_mut()
+    }
+
+    pub(super) fn operand(&mut self, value: i64) {
+        if let Some(e) = self.entry() {
+            e.operands.push(value);
+        }
+    }
+
+    pub(super) fn write(&mut self, address: usize, value: i64) {
+        if let Some(e) = self.entry() {
+            e.writes.push((address, value));
+        }
+    }
+
+    pub(super) fn relative_base(&mut self, from: i64, to: i64) {
+        if let Some(e) = self.entry() {
+            e.relative_base = Some((from, to));
+        }
+    }
+
+    pub(super) fn input(&mut self, value: i64) {
+        if let Some(e) = self.entry() {
+            e.input = Some(value);
+        }
+    }
+
+    pub(super) fn output(&mut self, value: i64) {
+        if let Some(e) = self.entry() {
+            e.output = Some(value);
+        }
+    }
+
+    pub(super) fn discard(&mut self) {
+        self.entry = None;
+    }
+
+    pub(super) fn finish(&mut self) -> std::io::Result<()> {
+        let e = match self.entry.take() {
+            Some(e) => e,
+            None => return Ok(()),
+        };
+        let operands = e
+            .operands
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>()
+            .join(",");
+        let writes = e
+            .writes
+            .iter()
+            .map(|(a, v)| format!("[{},{}]", a, v))
+            .collect::<Vec<String>>()
+            .join(",");
+        let relative_base = match e.relative_base {
+            Some((from, to)) => format!("[{},{}]", from, to),
+            None => "null".to_string(),
+        };
+        writeln!(
+            self.out,
+            "{{\"step\":{},\"address\":{},\"instruction\":\"{}\",\"operands\":[{}],\"writes\":[{}],\"relative_base\":{},\"input\":{},\"output\":{}}}",
+            self.step,
+            e.address,
+            escape(&e.instruction),
+            operands,
+            writes,
+            relative_base,
+            optional(e.input),
+            optional(e.output),
+        )?;
+        self.step += 1;
+        Ok(())
+    }
+
+    pub(super) fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::opcodes::{Programm, StepOutcome};
+    use std::fs;
+
+    #[test]
+    fn writes_one_line_per_executed_instruction() {
+        let path = std::env::temp_dir().join(format!("trace-{}.jsonl", std::process::id()));
+        let mut programm = "109,5,3,100,4,100,99".parse::<Programm>().unwrap();
+        programm.trace_to(&path).unwrap();
+        assert_eq!(programm.step(), Ok(StepOutcome::Continued));
+        assert_eq!(programm.step(), Ok(StepOutcome::NeedInput));
+        programm.push_input(42);
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+        programm.stop_trace().unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines = text.lines().collect::<Vec<&str>>();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"step":0,"address":0,"instruction":"ARB #5","operands":[5],"writes":[],"relative_base":[0,5],"input":null,"output":null}"#,
+                r#"{"step":1,"address":2,"instruction":"IN -> [100]","operands":[],"writes":[[100,42]],"relative_base":null,"input":42,"output":null}"#,
+                r#"{"step":2,"address":4,"instruction":"OUT [100]","operands":[42],"writes":[],"relative_base":null,"input":null,"output":42}"#,
+                r#"{"step":3,"address":6,"instruction":"HLT","operands":[],"writes":[],"relative_base":null,"input":null,"output":null}"#,
+            ]
+        );
+    }
+}
@@ -0,0 +1,218 @@
+use super::Programm;
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Access {
+    Read,
+    Write,
+    Patch,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum WatchAction {
+    Notify,
+    Stop,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Watchpoint {
+    pub range: RangeInclusive<usize>,
+    pub access: Access,
+    pub action: WatchAction,
+}
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct Hit {
+    pub address: usize,
+    pub target: usize,
+    pub access: Access,
+    pub value: i64,
+}
+
+impl Display for Hit {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let verb = match self.access {
+            Access::Read => "reads",
+            Access::Write => "writes",
+            Access::Patch => "patches executed code at",
+        };
+        write!(
+            f,
+            "instruction at {} {} [{}] = {}",
+            self.address, verb, self.target, self.value
+        )
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(super) struct Watches {
+    points: Vec<Watchpoint>,
+    executed: BTreeSet<usize>,
+    hits: Vec<Hit>,
+    stopped: bool,
+}
+
+impl Watches {
+    pub(super) fn executing(&mut self, position: usize, length: usize) {
+        if !self.points.iter().any(|p| p.access == Access::Patch) {
+            return;
+        }
+        self.executed.extend(position..position + length);
+    }
+
+    pub(super) fn access(&mut self, address: usize, access: Access, target: usize, value: i64) {
+        let patch = access == Access::Write && self.executed.contains(&target);
+        for point in self.points.iter() {
+            let matches = point.access == access || (patch && point.access == Access::Patch);
+            if matches && point.range.contains(&target) {
+                self.hits.push(Hit {
+                    address,
+                    target,
+                    access: point.access,
+                    value,
+                });
+                if point.action == WatchAction::Stop {
+                    self.stopped = true;
+                }
+            }
+        }
+    }
+
+    pub(super) fn executed_ranges(&self) -> Vec<RangeInclusive<usize>> {
+        let mut result: Vec<RangeInclusive<usize>> = vec![];
+        for &address in self.executed.iter() {
+            match result.last_mut() {
+                Some(range) if *range.end() + 1 == address => *range = *range.start()..=address,
+                _ => result.push(address..=address),
+            }
+        }
+        result
+    }
+
+    pub(super) fn take_stop(&mut self) -> bool {
+        std::mem::take(&mut self.stopped)
+    }
+}
+
+impl Programm {
+    pub fn watch(&mut self, range: RangeInclusive<usize>, access: Access, action: WatchAction) {
+        self.watches
+            .get_or_insert_with(Box::default)
+            .points
+            .push(Watchpoint {
+                range,
+                access,
+                action,
+            });
+    }
+
+    pub fn detect_self_modification(&mut self, action: WatchAction) {
+        self.watch(0..=usize::MAX, Access::Patch, action);
+    }
+
+    pub fn watchpoints(&self) -> &[Watchpoint] {
+        self.watches.as_ref().map_or(&[], |w| &w.points)
+    }
+
+    pub fn unwatch(&mut self, index: usize) -> Option<Watchpoint> {
+        let watches = self.watches.as_mut()?;
+        if index < watches.points.len() {
+            Some(watches.points.remove(index))
+        } else {
+            None
+        }
+    }
+
+    pub fn take_hits(&mut self) -> Vec<Hit> {
+        self.watches
+            .as_mut()
+            .map(|w| std::mem::take(&mut w.hits))
+            .unwrap_or_default()
+    }
+
+    pub(super) fn watched(&mut self, access: Access, target: usize, value: i64) {
+        let address = self.position;
+        if let Some(watches) = self.watches.as_mut() {
+            watches.access(address, access, target, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::opcodes::StepOutcome;
+
+    fn programm(code: &str) -> Programm {
+        code.parse::<Programm>().unwrap()
+    }
+
+    #[test]
+    fn notifies_on_writes_in_range() {
+        let mut programm = programm("1101,1,2,5,99,0");
+        programm.watch(5..=6, Access::Write, WatchAction::Notify);
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+        assert_eq!(
+            programm.take_hits(),
+            vec![Hit {
+                address: 0,
+                target: 5,
+                access: Access::Write,
+                value: 3
+            }]
+        );
+    }
+
+    #[test]
+    fn stops_after_a_watched_read() {
+        let mut programm = programm("4,3,99,7");
+        programm.watch(3..=3, Access::Read, WatchAction::Stop);
+        assert_eq!(programm.step(), Ok(StepOutcome::Output(7)));
+        assert_eq!(programm.step(), Ok(StepOutcome::Watchpoint));
+        assert_eq!(programm.take_hits().len(), 1);
+        assert_eq!(programm.step(), Ok(StepOutcome::Halted));
+    }
+
+    #[test]
+    fn detects_writes_to_executed_code() {
+        let mut programm = programm("1101,1,1,0,99");
+        programm.detect_self_modification(WatchAction::Notify);
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+        assert_eq!(
+            programm.take_hits(),
+            vec![Hit {
+                address: 0,
+                target: 0,
+                access: Access::Patch,
+                value: 2
+            }]
+        );
+    }
+
+    #[test]
+    fn ignores_writes_to_data_and_patches_before_execution() {
+        let mut programm = programm("1101,1,1,5,99,0");
+        programm.detect_self_modification(WatchAction::Stop);
+        programm.alter(1, 4).unwrap();
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+        assert_eq!(programm.read(5), 5);
+        assert!(programm.take_hits().is_empty());
+    }
+
+    #[test]
+    fn tracks_code_at_sparse_addresses() {
+        let far = 1usize << 40;
+        let mut programm = programm(&format!("1105,1,{},99", far));
+        programm.detect_self_modification(WatchAction::Notify);
+        programm.alter(far, 1101).unwrap();
+        for (i, value) in [1, 1, far as i64, 99].iter().enumerate() {
+            programm.alter(far + 1 + i, *value).unwrap();
+        }
+        assert_eq!(programm.run_until(|_| false), Ok(StepOutcome::Halted));
+        assert_eq!(programm.take_hits()[0].target, far);
+        let watches = programm.watches.as_ref().unwrap();
+        assert_eq!(watches.executed_ranges(), vec![0..=2, far..=far + 4]);
+    }
+}
@@ -1,4 +1,4 @@
-use crate::opcodes::Programm;
+use crate::opcodes::{Programm, StepOutcome, VmError};
 extern crate gif;
 extern crate termion;
 use std::collections::HashMap;
@@ -154,9 +154,28 @@ impl Plane {
         c.painted
     }
 
-    fn parse_results(&mut self, r: Vec<i64>) -> bool {
-        let color = r[0];
-        let orientation = r[1];
+    fn next_output(&mut self) -> Result<Option<i64>, VmError> {
+        match self.programm.run_until(|o| o != &StepOutcome::Continued)? {
+            StepOutcome::Output(value) => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    fn turn(&mut self) -> Result<Option<bool>, VmError> {
+        if self.programm.run_until(|o| o != &StepOutcome::Continued)? != StepOutcome::NeedInput {
+            return Ok(None);
+        }
+        let current_color = self.current_color();
+        self.programm.push_input(current_color);
+        let color = self.next_output()?;
+        let orientation = self.next_output()?;
+        Ok(match (color, orientation) {
+            (Some(color), Some(orientation)) => Some(self.parse_results(color, orientation)),
+            _ => None,
+        })
+    }
+
+    fn parse_results(&mut self, color: i64, orientation: i64) -> bool {
         let color = if color == 0 {
             Color::Black
         } else {
@@ -174,16 +193,9 @@ impl Plane {
 }
 
 impl Iterator for Plane {
-    type Item = bool;
-    fn next(&mut self) -> Option<bool> {
-        if self.programm.is_finished() {
-            None
-        } else {
-            let current_color = self.current_color();
-            let result = self.programm.run(&mut vec![current_color]);
-            let result = self.parse_results(result);
-            Some(result)
-        }
+    type Item = Result<bool, VmError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.turn().transpose()
     }
 }
 
@@ -227,7 +239,8 @@ pub fn run() {
     let programm = buffer.parse::<Programm>().unwrap();
 
     let plane = Plane::new(programm);
-    let result = plane.filter(|o| *o).count();
+    let painted = plane.collect::<Result<Vec<bool>, VmError>>().unwrap();
+    let result = painted.iter().filter(|o| **o).count();
     println!("Result: {}", result);
 }
 
@@ -240,7 +253,7 @@ pub fn run_e() {
     let programm = buffer.parse::<Programm>().unwrap();
 
     let plane = &mut Plane::with_color(programm, Color::White);
-    let _result = plane.all(|_| true);
+    let _result = plane.collect::<Result<Vec<bool>, VmError>>().unwrap();
 
     println!("{}", plane);
 }
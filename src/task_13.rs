@@ -1,4 +1,4 @@
-use crate::opcodes::Programm;
+use crate::opcodes::{Programm, StepOutcome, VmError};
 use std::collections::HashMap;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -50,19 +50,18 @@ struct ArcadeCabinet {
     paddle_position: Coordinate,
     played: bool,
     score: i64,
-    input: Vec<i64>,
 }
 
 impl ArcadeCabinet {
-    fn play(&mut self) {
+    fn play(&mut self) -> Result<(), VmError> {
         if !self.played {
-            self.programm.alter(0, 2);
+            self.programm.alter(0, 2)?;
             self.played = true;
         }
         while !self.programm.is_finished() {
-            self.fill_layout();
-            self.input
-                .push(if self.paddle_position.x < self.ball_position.x {
+            self.fill_layout()?;
+            self.programm
+                .push_input(if self.paddle_position.x < self.ball_position.x {
                     1
                 } else if self.paddle_position.x > self.ball_position.x {
                     -1
@@ -70,16 +69,23 @@ impl ArcadeCabinet {
                     0
                 })
         }
+        Ok(())
     }
 
-    fn fill_layout(&mut self) {
-        let mut iter = self.programm.run(&mut self.input).into_iter();
-        while let Some(x) = iter.next() {
-            if let Some(y) = iter.next() {
+    fn next_output(&mut self) -> Result<Option<i64>, VmError> {
+        match self.programm.run_until(|o| o != &StepOutcome::Continued)? {
+            StepOutcome::Output(value) => Ok(Some(value)),
+            _ => Ok(None),
+        }
+    }
+
+    fn fill_layout(&mut self) -> Result<(), VmError> {
+        while let Some(x) = self.next_output()? {
+            if let Some(y) = self.next_output()? {
                 if x == -1 && y == 0 {
-                    self.score = iter.next().unwrap();
+                    self.score = self.next_output()?.unwrap();
                 } else {
-                    if let Some(o) = iter.next().map(|o| match o {
+                    if let Some(o) = self.next_output()?.map(|o| match o {
                         1 => Object::Wall,
                         2 => Object::Block,
                         3 => Object::HorizontalPaddle,
@@ -97,6 +103,7 @@ impl ArcadeCabinet {
                 }
             }
         }
+        Ok(())
     }
 
     fn count_blocks(&self) -> usize {
@@ -120,7 +127,6 @@ impl FromStr for ArcadeCabinet {
             ball_position: Coordinate::empty(),
             played: false,
             score: 0,
-            input: vec![],
         })
     }
 }
@@ -133,7 +139,7 @@ pub fn run() {
 
     let mut cabinet: ArcadeCabinet = buffer.parse().unwrap();
 
-    cabinet.fill_layout();
+    cabinet.fill_layout().unwrap();
     let result = cabinet.count_blocks();
     println!("Result: {}", result);
 }
@@ -146,6 +152,6 @@ pub fn run_e() {
 
     let mut cabinet: ArcadeCabinet = buffer.parse().unwrap();
 
-    cabinet.play();
+    cabinet.play().unwrap();
     println!("Result: {}", cabinet.score);
 }
@@ -1,4 +1,4 @@
-use crate::opcodes::Programm;
+use crate::opcodes::{Programm, StepOutcome};
 use std::collections::HashMap;
 use std::fmt::Display;
 use std::fs::File;
@@ -202,6 +202,14 @@ impl Maze {
         }
     }
 
+    fn send(programm: &mut Programm, command: i64) -> i64 {
+        programm.push_input(command);
+        match programm.run_until(|o| o != &StepOutcome::Continued) {
+            Ok(StepOutcome::Output(status)) => status,
+            _ => panic!("Droid stopped responding"),
+        }
+    }
+
     fn run(
         maze: &mut Maze,
         programm: &mut Programm,
@@ -219,7 +227,7 @@ impl Maze {
             if maze.layout.contains_key(&coord) {
                 continue;
             }
-            let r = programm.run(&mut vec![i])[0];
+            let r = Maze::send(programm, i);
             if r == 0 {
                 maze.layout.insert(coord.clone(), Content::Wall);
             // do nothing and stop
@@ -242,7 +250,7 @@ impl Maze {
                     3 => 4,
                     _ => 3,
                 };
-                programm.run(&mut vec![j]);
+                Maze::send(programm, j);
             } else {
                 maze.layout.insert(coord.clone(), Content::Target);
                 this_min = Some(1);
@@ -252,7 +260,7 @@ impl Maze {
                     3 => 4,
                     _ => 3,
                 };
-                programm.run(&mut vec![j]);
+                Maze::send(programm, j);
             }
         }
         this_min
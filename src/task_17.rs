@@ -1,3 +1,4 @@
+use crate::opcodes::ascii::{self, Ascii};
 use crate::opcodes::Programm;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -87,13 +88,10 @@ pub fn run() {
     let mut buffer = String::new();
     input.read_to_string(&mut buffer).unwrap();
 
-    let mut programm = buffer.parse::<Programm>().unwrap();
+    let programm = buffer.parse::<Programm>().unwrap();
 
-    let layout = programm
-        .run(&mut vec![])
-        .into_iter()
-        .map(|c| std::char::from_u32(c as u32).unwrap())
-        .collect::<String>()
+    let mut robot = Ascii::new(programm);
+    let layout = ascii::text(&robot.run().unwrap())
         .parse::<Layout>()
         .unwrap();
 
@@ -1,79 +1,70 @@
+use crate::opcodes::parallel::Parallel;
+use crate::opcodes::sweep::Sweep;
+use crate::opcodes::symbolic::{Symbolic, Value};
+use crate::opcodes::{Programm, StepOutcome};
 use std::fs::File;
 use std::io::{BufReader, Read};
 
-fn evaluate(data: &mut Vec<i32>, position: usize) {
-    match data.get(position) {
-        Some(1) => {
-            let left = data[position + 1] as usize;
-            let right = data[position + 2] as usize;
-            let result = data[position + 3] as usize;
-            data[result] = data[left] + data[right];
-            evaluate(data, position + 4);
-        }
-        Some(2) => {
-            let left = data[position + 1] as usize;
-            let right = data[position + 2] as usize;
-            let result = data[position + 3] as usize;
-            data[result] = data[left] * data[right];
-            evaluate(data, position + 4);
-        }
-        _ => (),
-    };
-}
-
-pub fn run() {
+fn load() -> Programm {
     let input = File::open("input/task_2").unwrap();
     let mut input = BufReader::new(input);
     let mut line = String::new();
 
     input.read_to_string(&mut line).unwrap();
 
-    let mut result = line
-        .split(",")
-        .filter_map(|l| l.parse::<i32>().ok())
-        .collect::<Vec<i32>>();
+    line.parse::<Programm>().unwrap()
+}
+
+pub fn run() {
+    let mut programm = load();
 
-    result[1] = 12;
-    result[2] = 2;
+    programm.alter(1, 12).unwrap();
+    programm.alter(2, 2).unwrap();
 
-    evaluate(&mut result, 0);
+    programm.run(&mut vec![]).unwrap();
 
-    println!("Result: {}", result[0]);
+    println!("Result: {}", programm.read(0));
 }
 
-fn evaluate_e(data: Vec<i32>, target: i32) -> i32 {
-    (0..=99)
-        .flat_map(move |left| (0..=99).map(move |right| (left, right)))
-        .map(|(left, right)| {
-            let mut input = data.clone();
-            input[1] = left;
-            input[2] = right;
-            evaluate(&mut input, 0);
-            (left, right, input[0])
-        })
-        .find_map(|(left, right, result)| {
-            if result == target {
-                Some(100 * left + right)
-            } else {
-                None
-            }
+fn solve_symbolic(programm: &Programm, target: i64) -> Result<i64, String> {
+    let mut machine = Symbolic::new(programm);
+    machine.symbol_at(1, "noun");
+    machine.symbol_at(2, "verb");
+    machine.run(100_000).map_err(|e| e.to_string())?;
+    let result = match machine.memory(0) {
+        Value::Linear(e) => e,
+        Value::Opaque(s) => return Err(format!("result {} is not linear", s)),
+    };
+    let solutions = result.solve(target, &[("noun", 0..=99), ("verb", 0..=99)])?;
+    solutions
+        .first()
+        .map(|s| 100 * s["noun"] + s["verb"])
+        .ok_or(format!("{} never equals {}", result, target))
+}
+
+fn evaluate_e(programm: &Programm, target: i64) -> i64 {
+    let run = Sweep::new(programm)
+        .patch(1, 0..=99)
+        .patch(2, 0..=99)
+        .with_fuel(100_000)
+        .par_find_first(&Parallel::default(), |r| {
+            r.result == Ok(StepOutcome::Halted) && r.programm.read(0) == target
         })
         .unwrap()
+        .unwrap();
+    100 * run.values[0] + run.values[1]
 }
 
 pub fn run_e() {
-    let input = File::open("input/task_2").unwrap();
-    let mut input = BufReader::new(input);
-    let mut line = String::new();
+    let programm = load();
 
-    input.read_to_string(&mut line).unwrap();
-
-    let result = line
-        .split(",")
-        .filter_map(|l| l.parse::<i32>().ok())
-        .collect::<Vec<i32>>();
-
-    let result = evaluate_e(result, 19690720);
+    let result = match solve_symbolic(&programm, 19690720) {
+        Ok(result) => result,
+        Err(e) => {
+            println!("Symbolic solve failed: {}, falling back to search", e);
+            evaluate_e(&programm, 19690720)
+        }
+    };
 
     println!("Result: {}", result);
 }
@@ -11,7 +11,7 @@ pub fn run() {
 
     let mut programm = buffer.parse::<Programm>().unwrap();
 
-    let result = programm.run(&mut vec![1]);
+    let result = programm.run(&mut vec![1]).unwrap();
 
     println!("Result: {}", result.last().unwrap())
 }
@@ -25,7 +25,7 @@ pub fn run_e() {
 
     let mut programm = buffer.parse::<Programm>().unwrap();
 
-    let result = programm.run(&mut vec![5]);
+    let result = programm.run(&mut vec![5]).unwrap();
 
     println!("Result: {}", result.last().unwrap())
 }
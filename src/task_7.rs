@@ -1,3 +1,5 @@
+use crate::opcodes::network::Network;
+use crate::opcodes::parallel::Parallel;
 use crate::opcodes::Programm;
 use std::fs::File;
 use std::io::{BufReader, Read};
@@ -13,43 +15,33 @@ impl ProgrammSet {
         }
     }
 
+    fn network(&self, phases: Vec<i64>, loopback: bool) -> Network {
+        let mut network = Network::new();
+        for (programm, phase) in self.p.iter().zip(phases) {
+            let node = network.add(programm.clone());
+            network.send(node, phase);
+        }
+        let count = self.p.len();
+        for i in 1..count {
+            network.connect(i - 1, i);
+        }
+        if loopback {
+            network.connect(count - 1, 0);
+        }
+        network.send(0, 0);
+        network
+    }
+
     fn run(&mut self, input: Vec<i64>) -> i64 {
-        let mut i = input.clone();
-        i.insert(i.len() - 1, 0);
-        println!("acc: {:?}", i);
-        self.p.iter_mut().fold(i, |mut acc, programm| {
-            let r = programm.run(&mut acc);
-            let p = if acc.len() >= 1 { acc.len() - 1 } else { 0 };
-            acc.insert(p, r[0]);
-            acc
-        })[0]
+        let reports = self.network(input, false).run();
+        *reports.last().unwrap().outputs.last().unwrap()
     }
 
     fn run_with_loopback(&mut self, input: Vec<i64>) -> i64 {
-        let mut inp = input.iter().map(|v| v + 5).collect::<Vec<i64>>();
-        let mut outputs = vec![0];
-        let mut finished = vec![false; self.p.len()];
-        let mut i = 0;
-        loop {
-            let mut v = vec![];
-            if let Some(input) = outputs.pop() {
-                v.push(input);
-            }
-            if let Some(input) = inp.pop() {
-                v.push(input);
-            }
-            let mut r = self.p[i].run(&mut v);
-            finished[i] = self.p[i].is_finished();
-
-            outputs.append(&mut r);
-            if finished.iter().all(|f| *f == true) {
-                return outputs[0];
-            }
-            i += 1;
-            if i == self.p.len() {
-                i = 0;
-            }
-        }
+        let phases = input.iter().map(|phase| phase + 5).collect();
+        let reports = self.network(phases, true).run();
+        assert!(reports.iter().all(|r| r.halted()));
+        *reports.last().unwrap().outputs.last().unwrap()
     }
 }
 
@@ -108,8 +100,10 @@ pub fn run() {
 
     let programm = buffer.parse::<Programm>().unwrap();
 
-    let result = InputGenerator::new(5)
-        .map(|input| ProgrammSet::new(5, &programm).run(input))
+    let phases = InputGenerator::new(5).collect::<Vec<Vec<i64>>>();
+    let result = Parallel::default()
+        .map(phases.len(), |i| ProgrammSet::new(5, &programm).run(phases[i].clone()))
+        .into_iter()
         .max()
         .unwrap();
     println!("Result: {}", result);
@@ -124,8 +118,12 @@ pub fn run_e() {
 
     let programm = buffer.parse::<Programm>().unwrap();
 
-    let result = InputGenerator::new(5)
-        .map(|input| ProgrammSet::new(5, &programm).run_with_loopback(input))
+    let phases = InputGenerator::new(5).collect::<Vec<Vec<i64>>>();
+    let result = Parallel::default()
+        .map(phases.len(), |i| {
+            ProgrammSet::new(5, &programm).run_with_loopback(phases[i].clone())
+        })
+        .into_iter()
         .max()
         .unwrap();
     println!("Result: {}", result);
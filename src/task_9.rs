@@ -10,7 +10,7 @@ pub fn run() {
 
     let mut programm = buffer.parse::<Programm>().unwrap();
 
-    let result = programm.run(&mut vec![1]);
+    let result = programm.run(&mut vec![1]).unwrap();
 
     println!("Result: {:?}", result);
 }
@@ -23,7 +23,7 @@ pub fn run_e() {
 
     let mut programm = buffer.parse::<Programm>().unwrap();
 
-    let result = programm.run(&mut vec![2]);
+    let result = programm.run(&mut vec![2]).unwrap();
 
     println!("Result: {:?}", result);
 }
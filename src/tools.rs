@@ -0,0 +1,548 @@
+use crate::compiled;
+use crate::debugger;
+use crate::opcodes::ascii::Ascii;
+use crate::opcodes::parallel::Parallel;
+use crate::opcodes::sweep::{Run, Sweep};
+use crate::opcodes::symbolic::Symbolic;
+use crate::opcodes::watch::WatchAction;
+use crate::opcodes::{aot, asm, cfg, conformance, decompile, disasm, lang};
+use crate::opcodes::{Arithmetic, IterInput, ParseMode, Programm, StepOutcome};
+use std::fs::File;
+use std::io::{BufReader, Read};
+use std::time::{Duration, Instant};
+
+const TOOLS: [&str; 15] = [
+    "aot",
+    "ascii",
+    "asm",
+    "bench",
+    "cfg",
+    "compile",
+    "conformance",
+    "debug",
+    "decompile",
+    "disasm",
+    "exec",
+    "profile",
+    "sweep",
+    "symbolic",
+    "trace",
+];
+
+pub fn is_tool(name: &str) -> bool {
+    TOOLS.contains(&name)
+}
+
+pub fn run(name: &str, args: &[String]) {
+    match name {
+        "aot" => run_aot(args),
+        "ascii" => run_ascii(args),
+        "asm" => run_asm(args),
+        "bench" => run_bench(args),
+        "cfg" => run_cfg(args),
+        "compile" => run_compile(args),
+        "conformance" => run_conformance(),
+        "debug" => debugger::run(args),
+        "decompile" => run_decompile(args),
+        "disasm" => run_disasm(args),
+        "exec" => run_exec(args),
+        "profile" => run_profile(args),
+        "sweep" => run_sweep(args),
+        "symbolic" => run_symbolic(args),
+        "trace" => run_trace(args),
+        _ => println!("Unresolved tool"),
+    }
+}
+
+fn read(path: &str) -> String {
+    let input = File::open(path).unwrap();
+    let mut input = BufReader::new(input);
+    let mut buffer = String::new();
+    input.read_to_string(&mut buffer).unwrap();
+    buffer
+}
+
+fn load(path: &str) -> Programm {
+    read(path)
+        .parse::<Programm>()
+        .unwrap_or_else(|e| panic!("{}: {}", path, e))
+}
+
+const VALUE_OPTIONS: [&str; 7] = [
+    "--arithmetic",
+    "--expect",
+    "--fuel",
+    "--memory",
+    "--rounds",
+    "--threads",
+    "--top",
+];
+
+fn inputs(args: &[String]) -> Vec<i64> {
+    let mut result = vec![];
+    let mut skip = false;
+    for a in args {
+        if skip {
+            skip = false;
+        } else if a.starts_with("--") {
+            skip = VALUE_OPTIONS.contains(&a.as_str());
+        } else if let Ok(v) = a.parse::<i64>() {
+            result.push(v);
+        }
+    }
+    result
+}
+
+fn option(args: &[String], name: &str) -> Option<usize> {
+    args.iter()
+        .position(|a| a == name)
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<usize>().ok())
+}
+
+fn run_ascii(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: ascii <file>");
+            return;
+        }
+    };
+    let stdin = std::io::stdin();
+    match Ascii::new(load(path)).interactive(stdin.lock()) {
+        Ok(StepOutcome::Halted) => println!("Halted"),
+        Ok(_) => println!("Input closed"),
+        Err(e) => println!("Error: {}", e),
+    }
+}
+
+fn run_exec(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: exec <file> [--memory <cells>] [--arithmetic wrapping|checked|big] [--fuel <steps>] [--detect-loops] [--self-modifying] [--lenient] [inputs]...");
+            println!("  --memory is granted in whole pages of 1024 cells");
+            return;
+        }
+    };
+    let mut programm = if args.iter().any(|a| a == "--lenient") {
+        Programm::parse(&read(path), ParseMode::Lenient).unwrap()
+    } else {
+        load(path)
+    };
+    if let Some(cells) = option(args, "--memory") {
+        programm = programm.with_memory_limit(cells);
+    }
+    let arithmetic = match args.iter().position(|a| a == "--arithmetic") {
+        Some(i) => match args.get(i + 1).map(|a| a.parse::<Arithmetic>()) {
+            Some(Ok(arithmetic)) => arithmetic,
+            Some(Err(e)) => return println!("{}", e),
+            None => return println!("--arithmetic needs a mode"),
+        },
+        None => Arithmetic::default(),
+    };
+    programm = programm.with_arithmetic(arithmetic);
+    if let Some(fuel) = option(args, "--fuel") {
+        programm = programm.with_fuel(fuel as u64);
+    }
+    if args.iter().any(|a| a == "--detect-loops") {
+        programm = programm.with_loop_detection();
+    }
+    if args.iter().any(|a| a == "--self-modifying") {
+        programm.detect_self_modification(WatchAction::Notify);
+    }
+    let mut outputs = vec![];
+    let result = programm.run_with(&mut IterInput(inputs(&args[1..]).into_iter()), &mut outputs);
+    let outputs = match arithmetic {
+        Arithmetic::Big => programm
+            .take_big_outputs()
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<String>>(),
+        _ => outputs.iter().map(|v| v.to_string()).collect(),
+    };
+    let outputs = outputs.join(", ");
+    match result {
+        Ok(StepOutcome::OutOfFuel) => println!("Out of fuel after [{}]", outputs),
+        Ok(StepOutcome::LoopDetected) => println!(
+            "Loop detected at {} after [{}]",
+            programm.position(),
+            outputs
+        ),
+        Ok(_) => println!("[{}]", outputs),
+        Err(e) => println!("Error: {} after [{}]", e, outputs),
+    }
+    for hit in programm.take_hits() {
+        println!("Self-modifying: {}", hit);
+    }
+    if let Some(fuel) = programm.fuel() {
+        println!("Fuel left: {}", fuel);
+    }
+}
+
+fn run_aot(args: &[String]) {
+    match args.first() {
+        Some(flag) if flag == "--verify" => {
+            let inputs = inputs(&args[1..]);
+            for module in compiled::MODULES.iter() {
+                let start = Instant::now();
+                let interpreted = Programm::from(module.image.to_vec()).run(&mut inputs.clone());
+                let interpreter = start.elapsed();
+                let start = Instant::now();
+                let _ = module.execute(&inputs);
+                let native = start.elapsed();
+                match module.verify(&inputs) {
+                    Ok(()) => println!(
+                        "ok    {} {:?}: interpreter {:.2?}, compiled {:.2?}",
+                        module.name,
+                        interpreted.unwrap_or_default(),
+                        interpreter,
+                        native
+                    ),
+                    Err(e) => println!("FAIL  {}: {}", module.name, e),
+                }
+            }
+        }
+        Some(path) => {
+            let name = std::path::Path::new(path)
+                .file_stem()
+                .map_or("programm".to_string(), |s| s.to_string_lossy().to_string());
+            print!("{}", aot::translate(&name, &load(path).image()));
+        }
+        None => println!("Usage: aot <file> | aot --verify [inputs]..."),
+    }
+}
+
+fn run_asm(args: &[String]) {
+    match args.first() {
+        Some(path) if args.len() > 1 && args[1] == "--run" => {
+            match Programm::assemble(&read(path)) {
+                Ok(mut programm) => {
+                    let mut outputs = vec![];
+                    let result = programm
+                        .run_with(&mut IterInput(inputs(&args[2..]).into_iter()), &mut outputs);
+                    println!("{:?} {:?}", result, outputs);
+                }
+                Err(e) => println!("{}: {}", path, e),
+            }
+        }
+        Some(path) => match asm::assemble(&read(path)) {
+            Ok(code) => println!("{}", asm::to_text(&code)),
+            Err(e) => println!("{}: {}", path, e),
+        },
+        None => println!("Usage: asm <file> [--run <inputs>...]"),
+    }
+}
+
+fn run_compile(args: &[String]) {
+    match args.first() {
+        Some(path) if args.len() > 1 && args[1] == "--run" => {
+            match Programm::compile(&read(path)) {
+                Ok(mut programm) => {
+                    let mut outputs = vec![];
+                    let result = programm
+                        .run_with(&mut IterInput(inputs(&args[2..]).into_iter()), &mut outputs);
+                    println!("{:?} {:?}", result, outputs);
+                }
+                Err(e) => println!("{}: {}", path, e),
+            }
+        }
+        Some(path) if args.len() > 1 && args[1] == "--asm" => match lang::compile(&read(path)) {
+            Ok(assembly) => print!("{}", assembly),
+            Err(e) => println!("{}: {}", path, e),
+        },
+        Some(path) => match lang::compile(&read(path)) {
+            Ok(assembly) => println!("{}", asm::to_text(&asm::assemble(&assembly).unwrap())),
+            Err(e) => println!("{}: {}", path, e),
+        },
+        None => println!("Usage: compile <file> [--asm | --run <inputs>...]"),
+    }
+}
+
+fn run_conformance() {
+    let cases = conformance::cases();
+    let mut failed = 0;
+    for case in cases.iter() {
+        match conformance::check(case) {
+            Ok(()) => println!("ok    {}", case.name),
+            Err(e) => {
+                failed += 1;
+                println!("FAIL  {}: {}", case.name, e);
+            }
+        }
+    }
+    println!("{} passed, {} failed", cases.len() - failed, failed);
+    if failed > 0 {
+        std::process::exit(1);
+    }
+}
+
+fn run_disasm(args: &[String]) {
+    let linear = args.iter().any(|a| a == "--linear");
+    match args.iter().find(|a| !a.starts_with("--")) {
+        Some(path) => {
+            let programm = load(path);
+            print!("{}", disasm::listing(&programm.disassemble(!linear)));
+        }
+        None => println!("Usage: disasm <file> [--linear]"),
+    }
+}
+
+fn run_profile(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: profile <file> [--top <n>] [--listing] [inputs]...");
+            return;
+        }
+    };
+    let mut programm = load(path);
+    programm.start_profile();
+    let mut outputs = vec![];
+    if let Err(e) = programm.run_with(&mut IterInput(inputs(&args[1..]).into_iter()), &mut outputs) {
+        println!("Error: {}", e);
+    }
+    println!("outputs {:?}", outputs);
+    let profile = programm.profile().unwrap();
+    print!("{}", profile.report(option(args, "--top").unwrap_or(10)));
+    if args.iter().any(|a| a == "--listing") {
+        println!();
+        print!("{}", profile.annotate(&programm.disassemble(true)));
+    }
+}
+
+fn run_cfg(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: cfg <file> [--resolve] [inputs]...");
+            return;
+        }
+    };
+    let programm = load(path);
+    let graph = if args.iter().any(|a| a == "--resolve") {
+        let mut recorded = programm.clone();
+        recorded.start_profile();
+        let mut outputs = vec![];
+        if let Err(e) = recorded.run_with(&mut IterInput(inputs(&args[1..]).into_iter()), &mut outputs) {
+            eprintln!("Error: {}", e);
+        }
+        cfg::build(&programm.image(), recorded.profile())
+    } else {
+        cfg::build(&programm.image(), None)
+    };
+    print!("{}", graph.to_dot());
+}
+
+fn run_decompile(args: &[String]) {
+    match args.first() {
+        Some(path) => print!("{}", decompile::decompile(&load(path).image())),
+        None => println!("Usage: decompile <file>"),
+    }
+}
+
+fn range(spec: &str) -> Option<std::ops::RangeInclusive<i64>> {
+    match spec.split_once("..") {
+        Some((start, end)) => Some(start.parse().ok()?..=end.parse().ok()?),
+        None => spec.parse().ok().map(|v| v..=v),
+    }
+}
+
+fn run_sweep(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: sweep <file> [<addr>=<lo>..<hi>]... [in=<lo>..<hi>]... [--fuel <steps>] [--threads <n>] [--expect <value>]");
+            return;
+        }
+    };
+    let mut sweep = Sweep::new(&load(path)).with_fuel(option(args, "--fuel").unwrap_or(1_000_000) as u64);
+    for spec in args[1..].iter().filter(|a| a.contains('=')) {
+        let (target, values) = spec.split_once('=').unwrap();
+        sweep = match (target.parse::<usize>(), range(values)) {
+            (_, Some(values)) if target == "in" => sweep.input(values),
+            (Ok(address), Some(values)) => sweep.patch(address, values),
+            _ => return println!("bad parameter `{}`", spec),
+        };
+    }
+    let expect = args
+        .iter()
+        .position(|a| a == "--expect")
+        .and_then(|i| args.get(i + 1))
+        .and_then(|v| v.parse::<i64>().ok());
+    let result = |r: &Run| r.outputs.last().cloned().unwrap_or_else(|| r.programm.read(0));
+    let mut parallel = Parallel::default();
+    if let Some(threads) = option(args, "--threads") {
+        parallel = parallel.with_threads(threads);
+    }
+    let matches = sweep.par_find_all(&parallel, |r| {
+        r.result == Ok(StepOutcome::Halted) && expect.is_none_or(|e| result(r) == e)
+    });
+    let matches = match matches {
+        Ok(matches) => matches,
+        Err(e) => return println!("{}", e),
+    };
+    for run in matches {
+        println!("{:?} -> {}", run.values, result(&run));
+    }
+}
+
+fn run_symbolic(args: &[String]) {
+    let path = match args.first() {
+        Some(path) => path,
+        None => {
+            println!("Usage: symbolic <file> [<addr>=<name>]... [<input>|<name>]...");
+            return;
+        }
+    };
+    let mut machine = Symbolic::new(&load(path));
+    for a in &args[1..] {
+        match a.split_once('=') {
+            Some((address, name)) => match address.parse::<usize>() {
+                Ok(address) => machine.symbol_at(address, name),
+                Err(_) => return println!("bad address in `{}`", a),
+            },
+            None => match a.parse::<i64>() {
+                Ok(value) => machine.push_input(value),
+                Err(_) => machine.push_symbol(a),
+            },
+        }
+    }
+    if let Err(e) = machine.run(10_000_000) {
+        println!("Error: {}", e);
+    }
+    for (i, output) in machine.outputs().iter().enumerate() {
+        println!("output {}: {}", i, output);
+    }
+    println!("memory 0: {}", machine.memory(0));
+}
+
+fn run_trace(args: &[String]) {
+    if args.len() < 2 {
+        println!("Usage: trace <file> <trace.jsonl> [inputs]...");
+        return;
+    }
+    let mut programm = load(&args[0]);
+    programm.trace_to(&args[1]).unwrap();
+    let mut outputs = vec![];
+    let result = programm.run_with(&mut IterInput(inputs(&args[2..]).into_iter()), &mut outputs);
+    println!("{:?} {:?}", result, outputs);
+    programm.stop_trace().unwrap();
+}
+
+fn permutations(values: Vec<i64>) -> Vec<Vec<i64>> {
+    if values.len() <= 1 {
+        return vec![values];
+    }
+    let mut result = vec![];
+    for i in 0..values.len() {
+        let mut rest = values.clone();
+        let first = rest.remove(i);
+        for mut p in permutations(rest) {
+            p.insert(0, first);
+            result.push(p);
+        }
+    }
+    result
+}
+
+fn bench_sweep(programm: &Programm) -> i64 {
+    Sweep::new(programm)
+        .patch(1, 0..=99)
+        .patch(2, 0..=99)
+        .find_all(|r| r.programm.read(0) == 19_690_720)
+        .unwrap()
+        .last()
+        .map_or(0, |r| 100 * r.values[0] + r.values[1])
+}
+
+fn bench_parallel_sweep(programm: &Programm) -> i64 {
+    Sweep::new(programm)
+        .patch(1, 0..=99)
+        .patch(2, 0..=99)
+        .par_find_all(&Parallel::default(), |r| r.programm.read(0) == 19_690_720)
+        .unwrap()
+        .last()
+        .map_or(0, |r| 100 * r.values[0] + r.values[1])
+}
+
+fn bench_feedback(programm: &Programm) -> i64 {
+    permutations((5..10).collect())
+        .into_iter()
+        .map(|phases| {
+            let mut amps = phases
+                .iter()
+                .map(|phase| {
+                    let mut p = programm.clone();
+                    p.push_input(*phase);
+                    p
+                })
+                .collect::<Vec<Programm>>();
+            let mut signal = 0;
+            while !amps[4].is_finished() {
+                for amp in amps.iter_mut() {
+                    let mut inputs = vec![signal];
+                    if let Some(out) = amp.run(&mut inputs).unwrap().last() {
+                        signal = *out;
+                    }
+                }
+            }
+            signal
+        })
+        .max()
+        .unwrap()
+}
+
+fn bench_arcade(programm: &Programm) -> i64 {
+    let mut p = programm.clone();
+    p.alter(0, 2).unwrap();
+    let (mut ball, mut paddle, mut score) = (0_i64, 0_i64, 0);
+    while !p.is_finished() {
+        let out = p.run(&mut vec![(ball - paddle).signum()]).unwrap();
+        for chunk in out.chunks(3) {
+            match chunk {
+                [-1, 0, s] => score = *s,
+                [x, _, 3] => paddle = *x,
+                [x, _, 4] => ball = *x,
+                _ => (),
+            }
+        }
+    }
+    score
+}
+
+type Workload = (&'static str, &'static str, fn(&Programm) -> i64);
+
+fn run_bench(args: &[String]) {
+    let rounds = option(args, "--rounds").unwrap_or(3);
+    let workloads: Vec<Workload> = vec![
+        ("task 2 noun/verb sweep", "input/task_2", bench_sweep),
+        ("task 2 parallel sweep", "input/task_2", bench_parallel_sweep),
+        ("task 7 feedback loop", "input/task_7", bench_feedback),
+        ("task 9 boost", "input/task_9", |p| {
+            p.clone().run(&mut vec![2]).unwrap()[0]
+        }),
+        ("task 9 boost (aot)", "input/task_9", |_| {
+            let (_, machine) = compiled::find("task_9").unwrap().execute(&[2]);
+            machine.outputs()[0]
+        }),
+        ("task 13 arcade", "input/task_13", bench_arcade),
+    ];
+    for (name, path, workload) in workloads {
+        let programm = load(path);
+        let mut best = None;
+        let mut result = 0;
+        for _ in 0..rounds {
+            let start = Instant::now();
+            result = workload(&programm);
+            let elapsed = start.elapsed();
+            best = Some(best.map_or(elapsed, |b: Duration| b.min(elapsed)));
+        }
+        println!(
+            "{:<24} {:>10.2?}  (result {})",
+            name,
+            best.unwrap(),
+            result
+        );
+    }
+}